@@ -1,6 +1,6 @@
 use std::{collections::{BTreeMap, BTreeSet}, fmt::Display};
 use itertools::Itertools;
-use crate::parse::{BlockFlag, BlockFrag, IR};
+use crate::parse::{BlockFlag, BlockFrag, PropDecl, PropType, PropValue, IR};
 
 const BLOCK_FAM: &'static str = "BlockFamily";
 const BLOCKS: &'static str = "Block";
@@ -13,6 +13,8 @@ struct BlockEntry {
     name: String,
     families: BTreeSet<String>,
     flags: BTreeSet<BlockFlag>,
+    /// In order of precedence, the last value of a property is the one that counts
+    props: Vec<PropValue>,
 }
 
 impl PartialEq for BlockEntry {
@@ -107,7 +109,8 @@ fn generate_flags(blocks: &mut BTreeSet<BlockEntry>) -> String {
                     let depleted_block = BlockEntry {
                         name: format!("Depleted{block}"),
                         families: block.families.clone(),
-                        flags: block.flags.clone().into_iter().filter(|f| !matches!(f, BlockFlag::Renewable(_))).collect()
+                        flags: block.flags.clone().into_iter().filter(|f| !matches!(f, BlockFlag::Renewable(_))).collect(),
+                        props: block.props.clone(),
                     };
                     flag_fns.entry("depleted".to_string()).or_insert(MatchFn::new("depleted", &BLOCKS).with_default("*self")).arms.push(
                         format!("{BLOCKS}::{block} => {BLOCKS}::{depleted_block}")
@@ -124,7 +127,8 @@ fn generate_flags(blocks: &mut BTreeSet<BlockEntry>) -> String {
                     let lit_furnace = BlockEntry {
                         name: format!("{block}On"),
                         families: block.families.clone(),
-                        flags: block.flags.clone()
+                        flags: block.flags.clone(),
+                        props: block.props.clone(),
                     };
                     flag_fns.entry("on".to_string()).or_insert(MatchFn::new("on", &BLOCKS).with_default("*self")).arms.push(
                         format!("{BLOCKS}::{block} => {BLOCKS}::{lit_furnace}")
@@ -137,12 +141,6 @@ fn generate_flags(blocks: &mut BTreeSet<BlockEntry>) -> String {
                     );
                     generated_blocks.insert(lit_furnace);
                 },
-                _ => {
-                    let flag_name = format!("is_{:?}", flag).to_lowercase();
-                    flag_fns.entry(flag_name.clone()).or_insert(MatchFn::new(&flag_name, "u32").with_default("true")).arms.push(
-                        format!("{BLOCKS}::{block} => true")
-                    );
-                }
             }
         }
     }
@@ -150,28 +148,130 @@ fn generate_flags(blocks: &mut BTreeSet<BlockEntry>) -> String {
     flag_fns.values().map(|match_fn| match_fn.to_rust(1)).join("\n\n")
 }
 
-pub fn generate(ir: &IR) -> String {
+/// `sound` -> `Sound`, `fertile_soil` -> `FertileSoil`
+fn enum_name(prop: &str) -> String {
+    prop.split('_').map(|word| {
+        let mut chars = word.chars();
+        chars.next().map(|c| c.to_ascii_uppercase().to_string() + chars.as_str()).unwrap_or_default()
+    }).collect()
+}
+
+fn prop_type(prop: &PropDecl) -> String {
+    match &prop.ty {
+        PropType::Float => "f32".to_string(),
+        PropType::Int => "i32".to_string(),
+        PropType::Bool => "bool".to_string(),
+        PropType::Enum(_) => enum_name(&prop.name),
+    }
+}
+
+/// The value as a Rust expression of the property's type
+fn prop_literal(prop: &PropDecl, value: &str) -> Result<String, String> {
+    let invalid = || format!("invalid value '{value}' for property {}", prop.name);
+    match &prop.ty {
+        PropType::Float => value.parse::<f32>().map(|v| format!("{v:?}")).map_err(|_| invalid()),
+        PropType::Int => value.parse::<i32>().map(|v| v.to_string()).map_err(|_| invalid()),
+        PropType::Bool => value.parse::<bool>().map(|v| v.to_string()).map_err(|_| invalid()),
+        PropType::Enum(variants) if variants.iter().any(|variant| variant == value) => Ok(format!("{}::{value}", enum_name(&prop.name))),
+        PropType::Enum(_) => Err(invalid()),
+    }
+}
+
+fn check_props<'a>(props: &[PropDecl], values: impl IntoIterator<Item = &'a PropValue>, owner: &str) -> Result<(), String> {
+    for value in values {
+        let prop = props.iter().find(|prop| prop.name == value.name)
+            .ok_or(format!("unknown property '{}' on {owner}", value.name))?;
+        prop_literal(prop, &value.value).map_err(|err| format!("{err} on {owner}"))?;
+    }
+    Ok(())
+}
+
+/// One accessor per property, `is_name` for bools; blocks sharing a value share a match arm
+fn generate_props(props: &[PropDecl], blocks: &BTreeSet<BlockEntry>) -> Result<String, String> {
+    let mut prop_fns = Vec::new();
+    for prop in props {
+        let default = prop_literal(prop, &prop.default)?;
+        let mut by_value: BTreeMap<String, Vec<&BlockEntry>> = BTreeMap::new();
+        for block in blocks {
+            let Some(value) = block.props.iter().rev().find(|value| value.name == prop.name) else {
+                continue;
+            };
+            let literal = prop_literal(prop, &value.value)?;
+            if literal != default {
+                by_value.entry(literal).or_default().push(block);
+            }
+        }
+        let fn_name = match prop.ty {
+            PropType::Bool => format!("is_{}", prop.name),
+            _ => prop.name.clone(),
+        };
+        let covered: usize = by_value.values().map(Vec::len).sum();
+        let match_fn = MatchFn::new(&fn_name, &prop_type(prop)).with_arms(
+            by_value.into_iter().map(|(literal, blocks)|
+                format!("{} => {literal}", blocks.into_iter().map(|block| format!("{BLOCKS}::{block}")).join(" | "))
+            ).collect()
+        );
+        let match_fn = if covered < blocks.len() { match_fn.with_default(&default) } else { match_fn };
+        prop_fns.push(match_fn.to_rust(1));
+    }
+    Ok(prop_fns.join("\n\n"))
+}
+
+pub fn generate(ir: &IR) -> Result<String, String> {
+    for (i, prop) in ir.props.iter().enumerate() {
+        if ir.props[..i].iter().any(|other| other.name == prop.name) {
+            return Err(format!("property {} is declared twice", prop.name));
+        }
+        if matches!(prop.ty, PropType::Enum(_)) && ir.sets.contains_key(&enum_name(&prop.name)) {
+            return Err(format!("property {} would define the same enum as a set", prop.name));
+        }
+    }
+    for (set, values) in ir.set_props.iter() {
+        check_props(&ir.props, values, &format!("set {set}"))?;
+    }
     let mut blocks: BTreeSet<BlockEntry> = BTreeSet::new();
     for block_pattern in ir.decl.iter() {
         let families = block_pattern.0.0.iter().filter_map(|frag| match frag { 
             BlockFrag::Ident(_) => None,
             BlockFrag::SetName(set_name) => Some(set_name.clone()) 
         }).collect::<BTreeSet<_>>();
-        for frags in block_pattern.0.0.iter()
+        // sets later in the pattern take precedence, then the block's own values
+        let mut props: Vec<PropValue> = block_pattern.0.0.iter().filter_map(|frag| match frag {
+            BlockFrag::Ident(_) => None,
+            BlockFrag::SetName(set_name) => ir.set_props.get(set_name),
+        }).flatten().cloned().collect();
+        props.extend(block_pattern.0.2.iter().cloned());
+        let frag_options = block_pattern.0.0.iter()
             .map(|frag| match frag {
-                BlockFrag::Ident(ident) => vec![ident],
-                BlockFrag::SetName(set_name) => ir.sets.get(set_name).unwrap().iter().collect()
-            }).multi_cartesian_product()
-        {
+                BlockFrag::Ident(ident) => Ok(vec![ident]),
+                BlockFrag::SetName(set_name) => ir.sets.get(set_name)
+                    .map(|variants| variants.iter().collect())
+                    .ok_or(format!("unknown set {set_name}")),
+            }).collect::<Result<Vec<_>, _>>()?;
+        for frags in frag_options.into_iter().multi_cartesian_product() {
             let block: String = frags.into_iter().map(|s| s.as_str()).collect();
-            blocks.insert(BlockEntry {
+            check_props(&ir.props, &block_pattern.0.2, &format!("block {block}"))?;
+            let entry = BlockEntry {
                 name: block,
                 families: families.clone(),
-                flags: block_pattern.0.1.clone()
-            });
+                flags: block_pattern.0.1.clone(),
+                props: props.clone(),
+            };
+            // declaring a block again adds to its families, flags and properties
+            let entry = match blocks.take(&entry) {
+                Some(mut existing) => {
+                    existing.families.extend(entry.families);
+                    existing.flags.extend(entry.flags);
+                    existing.props.extend(entry.props);
+                    existing
+                },
+                None => entry,
+            };
+            blocks.insert(entry);
         }
     }
     let flag_code = generate_flags(&mut blocks);
+    let prop_code = generate_props(&ir.props, &blocks)?;
     let mut code_blocks = Vec::new();
     code_blocks.push("use serde::{Deserialize, Serialize};".to_string());
     code_blocks.push("use strum_macros::{EnumIter, EnumString, Display};".to_string());
@@ -180,10 +280,48 @@ pub fn generate(ir: &IR) -> String {
     for (family, variants) in ir.sets.iter() {
         code_blocks.push(generate_enum(family, variants));
     }
+    for prop in ir.props.iter() {
+        if let PropType::Enum(variants) = &prop.ty {
+            code_blocks.push(generate_enum(&enum_name(&prop.name), &variants.iter().cloned().collect()));
+        }
+    }
     code_blocks.push(generate_enum(BLOCKS, &blocks));
     code_blocks.push(format!("impl {BLOCKS} {{"));
     code_blocks.push(flag_code);
+    code_blocks.push(prop_code);
     code_blocks.push(generate_family_impl(&blocks));
     code_blocks.push("}".to_string());
-    code_blocks.join("\n")
-}
\ No newline at end of file
+    Ok(code_blocks.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse::parse_file;
+    use super::generate;
+
+    #[test]
+    fn test_generate_props() {
+        let blockdef = r#"
+        prop friction: float = 1
+        prop light: int = 0
+        prop opaque: bool = true
+        prop sound: enum { Glass, Stone, Wood } = Stone
+
+        set Wood { Birch, Oak } sound(Wood)
+        block {Wood}Log
+        block Ice friction(0.05) sound(Glass)
+        block Glowstone light(12) opaque(false) sound(Glass)"#;
+        let (_, ir) = parse_file(blockdef).unwrap();
+        let code = generate(&ir).unwrap();
+        assert!(code.contains("pub enum Sound {\n\tGlass,\n\tStone,\n\tWood\n}"), "{code}");
+        for expected in [
+            "pub fn friction(&self) -> f32 {\n\t\tmatch self {\n\t\t\tBlock::Ice => 0.05,\n\t\t\t_ => 1.0\n\t\t}\n\t}",
+            "pub fn light(&self) -> i32 {\n\t\tmatch self {\n\t\t\tBlock::Glowstone => 12,\n\t\t\t_ => 0\n\t\t}\n\t}",
+            "pub fn is_opaque(&self) -> bool {\n\t\tmatch self {\n\t\t\tBlock::Glowstone => false,\n\t\t\t_ => true\n\t\t}\n\t}",
+            // every block has a value, the default arm would be unreachable
+            "pub fn sound(&self) -> Sound {\n\t\tmatch self {\n\t\t\tBlock::Glowstone | Block::Ice => Sound::Glass,\n\t\t\tBlock::BirchLog | Block::OakLog => Sound::Wood\n\t\t}\n\t}",
+        ] {
+            assert!(code.contains(expected), "missing\n{expected}\nin\n{code}");
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use std::{collections::{BTreeMap, BTreeSet}, str::FromStr};
 
 use nom::{
-    bytes::complete::tag, character::complete::{alpha1, multispace0, multispace1, space0, space1, line_ending}, combinator::{eof, fail, opt}, error::{Error, ParseError}, multi::{many1, separated_list0, separated_list1}, sequence::{delimited, tuple}, IResult, InputTakeAtPosition
+    branch::alt, bytes::complete::{is_not, tag, take_while1}, character::complete::{alpha1, multispace0, multispace1, not_line_ending, space0, space1, line_ending}, combinator::{eof, fail, opt}, error::{Error, ParseError}, multi::{many0, many1, separated_list0, separated_list1}, sequence::{delimited, preceded, tuple}, IResult, InputTakeAtPosition
 };
 use ron::de::SpannedError;
 use serde::{Deserialize, Serialize};
@@ -16,7 +16,6 @@ pub(crate) enum BlockFrag {
 #[serde(rename_all = "lowercase")]
 pub enum BlockFlag {
     Renewable(u32),
-    Transparent,
     Furnace(u32)
 }
 
@@ -28,49 +27,97 @@ impl FromStr for BlockFlag {
     }
 }
 
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub(crate) enum PropType {
+    Float,
+    Int,
+    Bool,
+    Enum(Vec<String>),
+}
+
+/// `prop name: type = default`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub(crate) struct PropDecl {
+    pub(crate) name: String,
+    pub(crate) ty: PropType,
+    pub(crate) default: String,
+}
+
+/// `name(value)`, or `name` alone for `name(true)`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub(crate) struct PropValue {
+    pub(crate) name: String,
+    pub(crate) value: String,
+}
+
 #[derive(Debug, PartialEq, Eq)]
-pub(crate) struct AddBlock(pub(crate) (Vec<BlockFrag>, BTreeSet<BlockFlag>));
+pub(crate) struct AddBlock(pub(crate) (Vec<BlockFrag>, BTreeSet<BlockFlag>, Vec<PropValue>));
 
 #[derive(Debug)]
 struct BlockSet {
     name: String,
     variants: BTreeSet<String>,
+    props: Vec<PropValue>,
 }
 
 #[derive(Debug)]
 pub(crate) struct IR {
     pub(crate) sets: BTreeMap<String, BTreeSet<String>>,
+    /// Property values given to every block of a set, before the block's own
+    pub(crate) set_props: BTreeMap<String, Vec<PropValue>>,
+    pub(crate) props: Vec<PropDecl>,
     pub(crate) decl: Vec<AddBlock>,
 }
 
 #[derive(Debug)]
-enum Either<Left, Right> {
-    Left(Left),
-    Right(Right),
+enum Statement {
+    Set(BlockSet),
+    Block(AddBlock),
+    Prop(PropDecl),
+}
+
+#[derive(Debug)]
+enum BlockAttr {
+    Flag(BlockFlag),
+    Prop(PropValue),
 }
 
 pub fn parse_file(input: &str) -> IResult<&str, IR> {
-    let (input, res): (&str, _) = many1(ws(parse_statement))(input)?;
+    let (input, res): (&str, _) = many1(delimited(blank, parse_statement, blank))(input)?;
     let (input, _) = eof(input)?;
     let mut sets = BTreeMap::new();
+    let mut set_props = BTreeMap::new();
+    let mut props = Vec::new();
     let mut decl = Vec::new();
-    for either in res {
-        match either {
-            Either::Left(block_set) => { sets.insert(block_set.name, block_set.variants); }
-            Either::Right(add_block) => { decl.push(add_block); }
+    for statement in res {
+        match statement {
+            Statement::Set(block_set) => {
+                set_props.insert(block_set.name.clone(), block_set.props);
+                sets.insert(block_set.name, block_set.variants);
+            }
+            Statement::Block(add_block) => { decl.push(add_block); }
+            Statement::Prop(prop) => { props.push(prop); }
         }
     }
-    Ok((input, IR { sets, decl }))
+    Ok((input, IR { sets, set_props, props, decl }))
+}
+
+/// Whitespace and `//` comments between statements
+fn blank(input: &str) -> IResult<&str, ()> {
+    let (input, _) = multispace0(input)?;
+    let (input, _) = many0(tuple((tag("//"), not_line_ending, multispace0)))(input)?;
+    Ok((input, ()))
 }
 
 fn statement_end(input: &str) -> IResult<&str, ()> {
     line_ending(input).map(|(input, _)| (input, ())).or_else(|_: nom::Err<Error<&str>>| eof(input).map(|(input, _)| (input, ())))
 }
 
-fn parse_statement(input: &str) -> IResult<&str, Either<BlockSet, AddBlock>> {
+fn parse_statement(input: &str) -> IResult<&str, Statement> {
     let (input, stmt) = parse_set(input)
-        .map(|(input, set)| (input, Either::Left(set)))
-        .or_else(|_| parse_decl(input).map(|(input, decl)| (input, Either::Right(decl))))?;
+        .map(|(input, set)| (input, Statement::Set(set)))
+        .or_else(|_| parse_decl(input).map(|(input, decl)| (input, Statement::Block(decl))))
+        .or_else(|_| parse_prop_decl(input).map(|(input, prop)| (input, Statement::Prop(prop))))?;
     let (input, _) = space0(input)?;
     let (input, _) = statement_end(input)?;
     Ok((input, stmt))
@@ -86,16 +133,67 @@ fn parse_set(input: &str) -> IResult<&str, BlockSet> {
     let (input, variants) = separated_list1(ws(tag(",")), parse_ident)(input)?;
     let (input, _) = multispace0(input)?;
     let (input, _) = tag("}")(input)?;
-    Ok((input, BlockSet { name: name.to_string(), variants: BTreeSet::from_iter(variants.into_iter().map(String::from)) }))
+    let (input, props) = opt(preceded(space1, separated_list1(space1, parse_prop_value)))(input)?;
+    Ok((input, BlockSet {
+        name: name.to_string(),
+        variants: BTreeSet::from_iter(variants.into_iter().map(String::from)),
+        props: props.unwrap_or_default(),
+    }))
 }
 
 fn parse_decl(input: &str) -> IResult<&str, AddBlock> {
-    let (input, (_, _, block_pattern, flags_opt)) = tuple((tag("block"), space1, many1(parse_block_frag), opt(tuple((space1, parse_block_flags)))))(input)?;
-    let flags = match flags_opt {
-        None => BTreeSet::new(),
-        Some((_, flags)) => flags
-    };
-    Ok((input, AddBlock((block_pattern, flags))))
+    let (input, (_, _, block_pattern, attrs_opt)) = tuple((tag("block"), space1, many1(parse_block_frag), opt(tuple((space1, parse_block_attrs)))))(input)?;
+    let mut flags = BTreeSet::new();
+    let mut props = Vec::new();
+    for attr in attrs_opt.map(|(_, attrs)| attrs).unwrap_or_default() {
+        match attr {
+            BlockAttr::Flag(flag) => { flags.insert(flag); }
+            BlockAttr::Prop(prop) => props.push(prop),
+        }
+    }
+    Ok((input, AddBlock((block_pattern, flags, props))))
+}
+
+fn parse_prop_decl(input: &str) -> IResult<&str, PropDecl> {
+    let (input, (_, _, name, _, _, ty, _, _, default)) = tuple((
+        tag("prop"), space1, parse_prop_name, space0, tag(":"), ws(parse_prop_type), tag("="), space0, parse_prop_literal
+    ))(input)?;
+    Ok((input, PropDecl { name: name.to_string(), ty, default: default.to_string() }))
+}
+
+fn parse_prop_type(input: &str) -> IResult<&str, PropType> {
+    alt((
+        |input| tag("float")(input).map(|(input, _)| (input, PropType::Float)),
+        |input| tag("int")(input).map(|(input, _)| (input, PropType::Int)),
+        |input| tag("bool")(input).map(|(input, _)| (input, PropType::Bool)),
+        parse_enum_type,
+    ))(input)
+}
+
+fn parse_enum_type(input: &str) -> IResult<&str, PropType> {
+    let (input, _) = tag("enum")(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, _) = tag("{")(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, variants) = separated_list1(ws(tag(",")), parse_ident)(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, _) = tag("}")(input)?;
+    Ok((input, PropType::Enum(variants.into_iter().map(String::from).collect())))
+}
+
+fn parse_prop_value(input: &str) -> IResult<&str, PropValue> {
+    let (input, name) = parse_prop_name(input)?;
+    let (input, value) = opt(delimited(tag("("), ws(parse_prop_literal), tag(")")))(input)?;
+    Ok((input, PropValue { name: name.to_string(), value: value.unwrap_or("true").to_string() }))
+}
+
+fn parse_prop_name(input: &str) -> IResult<&str, &str> {
+    take_while1(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')(input)
+}
+
+fn parse_prop_literal(input: &str) -> IResult<&str, &str> {
+    let (input, literal) = is_not(" \t\r\n()")(input)?;
+    Ok((input, literal))
 }
 
 fn parse_block_frag(input: &str) -> IResult<&str, BlockFrag> {
@@ -116,9 +214,11 @@ fn parse_ident(input: &str) -> IResult<&str, &str> {
     alpha1(input)
 }
 
-fn parse_block_flags(input: &str) -> IResult<&str, BTreeSet<BlockFlag>> {
-    let (input, flags) = separated_list0(space1, parse_block_flag)(input)?;
-    Ok((input, BTreeSet::from_iter(flags)))
+fn parse_block_attrs(input: &str) -> IResult<&str, Vec<BlockAttr>> {
+    separated_list0(space1, |input| {
+        parse_block_flag(input).map(|(input, flag)| (input, BlockAttr::Flag(flag)))
+            .or_else(|_| parse_prop_value(input).map(|(input, prop)| (input, BlockAttr::Prop(prop))))
+    })(input)
 }
 
 fn parse_block_flag(input: &str) -> IResult<&str, BlockFlag> {
@@ -155,8 +255,8 @@ mod tests {
             Oak,
             Spruce
         }"#;
-        let (input, IR) = parse_set(blockdef).unwrap();
-        println!("{IR:?}");
+        let (_, set) = parse_set(blockdef).unwrap();
+        println!("{set:?}");
     }
 
     #[test]
@@ -172,20 +272,46 @@ mod tests {
         block Stripped{Wood}Log
         
         block IronOre renewable(10)"#;
-        let (input, IR) = parse_file(blockdef).unwrap();
-        println!("{IR:?}");
+        let (_, ir) = parse_file(blockdef).unwrap();
+        println!("{ir:?}");
     }
 
     #[test]
     fn test_incorrect_flag() {
         let blockdef = r#"block IronOre apodhzipa"#;
-        assert!(parse_statement(&blockdef).is_err())
+        // parses as a property, which is only checked against the declared ones when generating
+        assert!(crate::generate_blocks(blockdef).is_err())
     }
 
     #[test]
     fn test_parse_flag() {
         let blockdef = r#"block IronOre renewable(10)"#;
         let (_, ir) = parse_decl(blockdef).unwrap();
-        assert_eq!(ir, AddBlock((vec![BlockFrag::Ident("IronOre".to_string())], BTreeSet::from([BlockFlag::Renewable(10)]))));
+        assert_eq!(ir, AddBlock((vec![BlockFrag::Ident("IronOre".to_string())], BTreeSet::from([BlockFlag::Renewable(10)]), Vec::new())));
+    }
+
+    #[test]
+    fn test_parse_props() {
+        let blockdef = r#"
+        // physical properties
+        prop friction: float = 1
+        prop sound: enum { Stone, Wood } = Stone
+        prop traversable: bool = false
+
+        set Plant { Grass, Lily } traversable sound(Wood)
+        block Ice friction(0.05) renewable(5)"#;
+        let (_, ir) = parse_file(blockdef).unwrap();
+        assert_eq!(ir.props[1], PropDecl {
+            name: "sound".to_string(),
+            ty: PropType::Enum(vec!["Stone".to_string(), "Wood".to_string()]),
+            default: "Stone".to_string(),
+        });
+        let prop = |name: &str, value: &str| PropValue { name: name.to_string(), value: value.to_string() };
+        assert_eq!(ir.set_props["Plant"], vec![prop("traversable", "true"), prop("sound", "Wood")]);
+        assert_eq!(ir.decl[0], AddBlock((
+            vec![BlockFrag::Ident("Ice".to_string())],
+            BTreeSet::from([BlockFlag::Renewable(5)]),
+            vec![prop("friction", "0.05")],
+        )));
     }
 }
@@ -1,9 +1,19 @@
 use std::ops::Range;
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use riverbed_closest::{points, ranges, ClosestTrait};
+use riverbed_closest::{points::{self, PointIndex}, ranges, ClosestTrait};
+
+/// Points spread over the unit hypercube by a small LCG, to compare the point collections at a larger size
+fn spread_points(count: usize) -> Vec<([f32; 4], usize)> {
+    let mut state: u32 = 0x5eed;
+    let mut next = move || {
+        state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+        (state >> 8) as f32 / (1 << 24) as f32
+    };
+    (0..count).map(|i| (core::array::from_fn(|_| next()), i)).collect()
+}
 
 fn range_closest(c: &mut Criterion) {
-    let imap: Vec<([Range<f32>; 4], String)> = ranges::from_csv("benches/plants_ranges.csv").unwrap();
+    let imap: Vec<([Range<f32>; 4], String)> = ranges::from_csv("benches/plants_ranges.csv").unwrap().0;
     let point = core::array::from_fn(|_| 0.5);
     c.bench_function(&format!("range-closest ({})", imap.len()), |b| b.iter(|| 
         black_box(imap.closest(point))
@@ -18,5 +28,25 @@ fn point_closest(c: &mut Criterion) {
     ));
 }
 
-criterion_group!(vec, range_closest, point_closest);
-criterion_main!(vec);
\ No newline at end of file
+fn point_index_closest(c: &mut Criterion) {
+    let imap: PointIndex<4, String> = points::from_csv("benches/plants_points.csv").unwrap().into();
+    let point = core::array::from_fn(|_| 0.5);
+    c.bench_function(&format!("point-index-closest ({})", imap.values().len()), |b| b.iter(|| 
+        black_box(imap.closest(point))
+    ));
+}
+
+fn point_closest_large(c: &mut Criterion) {
+    let points = spread_points(1000);
+    let index: PointIndex<4, usize> = points.clone().into();
+    let point = core::array::from_fn(|_| 0.5);
+    c.bench_function(&format!("point-closest ({})", points.len()), |b| b.iter(|| 
+        black_box(points.closest(point))
+    ));
+    c.bench_function(&format!("point-index-closest ({})", points.len()), |b| b.iter(|| 
+        black_box(index.closest(point))
+    ));
+}
+
+criterion_group!(vec, range_closest, point_closest, point_index_closest, point_closest_large);
+criterion_main!(vec);
@@ -0,0 +1,183 @@
+use std::collections::VecDeque;
+use crate::{error::LoadError, load, ranges::Ranges, utils::RangesUtil};
+
+/// Samples of [0; 1]^D, `step` apart on every axis, indexed with the first axis varying fastest
+struct Grid<const D: usize> {
+    side: usize,
+    step: f32,
+}
+
+impl<const D: usize> Grid<D> {
+    fn new(step: f32) -> Self {
+        Grid { side: (1./step).round() as usize + 1, step }
+    }
+
+    fn len(&self) -> usize {
+        self.side.pow(D as u32)
+    }
+
+    fn coords(&self, i: usize) -> [usize; D] {
+        core::array::from_fn(|d| i / self.side.pow(d as u32) % self.side)
+    }
+
+    fn point(&self, i: usize) -> [f32; D] {
+        self.coords(i).map(|c| (c as f32*self.step).min(1.))
+    }
+
+    fn neighbors(&self, i: usize) -> impl Iterator<Item = usize> + '_ {
+        let coords = self.coords(i);
+        (0..D).flat_map(move |d| {
+            let stride = self.side.pow(d as u32);
+            let before = (coords[d] > 0).then(|| i - stride);
+            let after = (coords[d] + 1 < self.side).then(|| i + stride);
+            before.into_iter().chain(after)
+        })
+    }
+}
+
+/// Index of the entry picked at the point and its score, ties go to the first entry like `ClosestTrait::closest`
+fn winner<const D: usize, E>(imap: &Ranges<D, E>, point: &[f32; D]) -> (usize, f32) {
+    let mut res = (0, f32::NEG_INFINITY);
+    for (i, (ranges, _)) in imap.iter().enumerate() {
+        let sign_dist = ranges.sign_dist(point);
+        if res.1 < sign_dist {
+            res = (i, sign_dist);
+        }
+    }
+    res
+}
+
+/// A connected part of the parameter space where no entry is suitable
+#[derive(Debug, Clone, PartialEq)]
+pub struct Region<const D: usize> {
+    pub min: [f32; D],
+    pub max: [f32; D],
+    /// Share of the parameter space in the region
+    pub share: f32,
+}
+
+/// An entry that is rarely picked inside its own ranges, because of another one
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shadowed<'a, E> {
+    pub entry: &'a E,
+    /// Share of the entry's ranges where it is picked
+    pub wins: f32,
+    pub by: &'a E,
+    /// Share of the entry's ranges where `by` is picked instead
+    pub lost: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report<'a, const D: usize, E> {
+    pub samples: usize,
+    /// Share of the parameter space where each entry is picked with a non negative score, best first
+    pub coverage: Vec<(&'a E, f32)>,
+    /// Largest first
+    pub uncovered: Vec<Region<D>>,
+    /// Entries picked in at most `max_wins` of their own ranges, least picked first
+    pub shadowed: Vec<Shadowed<'a, E>>,
+}
+
+/// Samples the parameter space every `step` to measure how a range table splits it
+pub fn analyze<const D: usize, E>(imap: &Ranges<D, E>, step: f32, max_wins: f32) -> Report<'_, D, E> {
+    let grid = Grid::<D>::new(step);
+    let n = imap.len();
+    let mut covered = vec![false; grid.len()];
+    let mut wins = vec![0usize; n];
+    // picked[i*n + j]: samples inside the ranges of i where j is picked
+    let mut picked = vec![0usize; n*n];
+    let mut inside = vec![0usize; n];
+    for (i, covered) in covered.iter_mut().enumerate() {
+        let point = grid.point(i);
+        let (best, score) = winner(imap, &point);
+        if score < 0. {
+            continue;
+        }
+        *covered = true;
+        wins[best] += 1;
+        for (j, (ranges, _)) in imap.iter().enumerate() {
+            if ranges.sign_dist(&point) >= 0. {
+                inside[j] += 1;
+                picked[j*n + best] += 1;
+            }
+        }
+    }
+    let samples = grid.len();
+    let mut coverage: Vec<_> = imap.iter().zip(&wins)
+        .map(|((_, value), count)| (value, *count as f32/samples as f32))
+        .collect();
+    coverage.sort_by(|(_, c1), (_, c2)| c2.total_cmp(c1));
+
+    // flood fill the uncovered samples into regions
+    let mut uncovered = Vec::new();
+    let mut seen = covered;
+    for start in 0..samples {
+        if seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        let mut region = Region { min: grid.point(start), max: grid.point(start), share: 0. };
+        let mut queue = VecDeque::from([start]);
+        while let Some(i) = queue.pop_front() {
+            let point = grid.point(i);
+            for ((min, max), p) in region.min.iter_mut().zip(region.max.iter_mut()).zip(point) {
+                *min = min.min(p);
+                *max = max.max(p);
+            }
+            region.share += 1.;
+            for neighbor in grid.neighbors(i) {
+                if !seen[neighbor] {
+                    seen[neighbor] = true;
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        region.share /= samples as f32;
+        uncovered.push(region);
+    }
+    uncovered.sort_by(|r1, r2| r2.share.total_cmp(&r1.share));
+
+    let mut shadowed = Vec::new();
+    for (i, (_, entry)) in imap.iter().enumerate() {
+        if inside[i] == 0 {
+            continue;
+        }
+        let row = &picked[i*n..(i+1)*n];
+        let wins = row[i] as f32/inside[i] as f32;
+        if wins > max_wins {
+            continue;
+        }
+        let Some((by, lost)) = row.iter().enumerate()
+            .filter(|(j, _)| *j != i)
+            .max_by_key(|(_, count)| **count)
+        else {
+            continue;
+        };
+        shadowed.push(Shadowed { entry, wins, by: &imap[by].1, lost: *lost as f32/inside[i] as f32 });
+    }
+    shadowed.sort_by(|s1, s2| s1.wins.total_cmp(&s2.wins));
+    Report { samples, coverage, uncovered, shadowed }
+}
+
+/// Index of the entry picked at each pixel of a `size`x`size` slice along dimensions `x` and `y`,
+/// the other dimensions being those of `at`; None where no entry is suitable.
+/// Rows go from y = 1 at the top to y = 0 at the bottom.
+pub fn slice<const D: usize, E>(imap: &Ranges<D, E>, x: usize, y: usize, at: [f32; D], size: usize) -> Vec<Option<usize>> {
+    let mut res = Vec::with_capacity(size*size);
+    let scale = (size.max(2) - 1) as f32;
+    for row in 0..size {
+        for col in 0..size {
+            let mut point = at;
+            point[x] = col as f32/scale;
+            point[y] = 1. - row as f32/scale;
+            let (best, score) = winner(imap, &point);
+            res.push((score >= 0.).then_some(best));
+        }
+    }
+    res
+}
+
+/// Names of the dimensions of a range or point file, from its CSV header or numbered for json5
+pub fn dimension_names(path: &str) -> Result<Vec<String>, LoadError> {
+    load::dimension_names(path)
+}
@@ -0,0 +1,129 @@
+//! Reports how a condition table splits the parameter space: coverage of each entry,
+//! regions where no entry is suitable and entries that other entries shadow.
+//!
+//! Usage: closest_report FILE [--step STEP] [--max-wins SHARE] [--slices DIR] [--at VALUE] [--size N]
+//! FILE is a CSV or json5 range table, --slices writes a png per pair of dimensions (requires the "image" feature),
+//! the dimensions outside of a slice are set to --at.
+use std::path::PathBuf;
+use anyhow::{bail, Context, Result};
+use riverbed_closest::{analysis::{self, Report}, ranges};
+
+struct Args {
+    path: String,
+    step: f32,
+    max_wins: f32,
+    slices: Option<PathBuf>,
+    at: f32,
+    size: usize,
+}
+
+fn parse_args() -> Result<Args> {
+    let mut argv = std::env::args().skip(1);
+    let mut args = Args {
+        path: argv.next().context("missing condition table path")?,
+        step: 0.05,
+        max_wins: 0.05,
+        slices: None,
+        at: 0.5,
+        size: 256,
+    };
+    while let Some(flag) = argv.next() {
+        let value = argv.next().with_context(|| format!("missing value for {flag}"))?;
+        match flag.as_str() {
+            "--step" => args.step = value.parse()?,
+            "--max-wins" => args.max_wins = value.parse()?,
+            "--slices" => args.slices = Some(PathBuf::from(value)),
+            "--at" => args.at = value.parse()?,
+            "--size" => args.size = value.parse()?,
+            _ => bail!("unknown argument {flag}"),
+        }
+    }
+    if !(args.step > 0. && args.step <= 1.) {
+        bail!("--step must be in ]0; 1]");
+    }
+    Ok(args)
+}
+
+fn print_report<const D: usize>(report: &Report<D, String>, names: &[String]) {
+    println!("samples: {}", report.samples);
+    println!("\n# coverage");
+    for (value, share) in report.coverage.iter() {
+        println!("{value}: {:.1}%", share*100.);
+    }
+    let covered: f32 = report.coverage.iter().map(|(_, share)| share).sum();
+    println!("---\nEmpty: {:.1}%", (1. - covered)*100.);
+    println!("\n# uncovered regions");
+    for region in report.uncovered.iter() {
+        let bounds: Vec<_> = names.iter().zip(region.min.iter().zip(region.max))
+            .map(|(name, (min, max))| format!("{name} {min:.2}..{max:.2}"))
+            .collect();
+        println!("{}: {:.1}%", bounds.join(", "), region.share*100.);
+    }
+    println!("\n# shadowed entries");
+    for shadowed in report.shadowed.iter() {
+        println!(
+            "{} is picked in {:.1}% of its ranges, {} in {:.1}%",
+            shadowed.entry, shadowed.wins*100., shadowed.by, shadowed.lost*100.
+        );
+    }
+}
+
+#[cfg(feature = "image")]
+fn write_slices<const D: usize>(imap: &ranges::Ranges<D, String>, names: &[String], args: &Args, dir: &std::path::Path) -> Result<()> {
+    use std::hash::{DefaultHasher, Hash, Hasher};
+    use image::{Rgb, RgbImage};
+    let colors: Vec<Rgb<u8>> = imap.iter().map(|(_, value)| {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        let [r, g, b, ..] = hasher.finish().to_le_bytes();
+        Rgb([r, g, b])
+    }).collect();
+    std::fs::create_dir_all(dir)?;
+    for x in 0..D {
+        for y in x+1..D {
+            let picks = analysis::slice(imap, x, y, [args.at; D], args.size);
+            let side = args.size as u32;
+            let image = RgbImage::from_fn(side, side, |col, row| {
+                picks[(row*side + col) as usize].map_or(Rgb([0, 0, 0]), |i| colors[i])
+            });
+            image.save(dir.join(format!("{}_{}.png", names[x], names[y])))?;
+        }
+    }
+    println!("\n# slice colors");
+    for ((_, value), Rgb([r, g, b])) in imap.iter().zip(colors) {
+        println!("{value}: #{r:02x}{g:02x}{b:02x}");
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "image"))]
+fn write_slices<const D: usize>(_: &ranges::Ranges<D, String>, _: &[String], _: &Args, _: &std::path::Path) -> Result<()> {
+    bail!("slices require the \"image\" feature")
+}
+
+fn run<const D: usize>(args: &Args, names: &[String]) -> Result<()> {
+    let (imap, warnings): (ranges::Ranges<D, String>, _) = ranges::from_file_checked(&args.path)?;
+    for warning in warnings {
+        println!("warning: {warning}");
+    }
+    println!("{}: {} entries, dimensions: {}", args.path, imap.len(), names.join(", "));
+    print_report(&analysis::analyze(&imap, args.step, args.max_wins), names);
+    if let Some(dir) = &args.slices {
+        write_slices(&imap, names, args, dir)?;
+    }
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let args = parse_args()?;
+    let names = analysis::dimension_names(&args.path)?;
+    match names.len() {
+        1 => run::<1>(&args, &names),
+        2 => run::<2>(&args, &names),
+        3 => run::<3>(&args, &names),
+        4 => run::<4>(&args, &names),
+        5 => run::<5>(&args, &names),
+        6 => run::<6>(&args, &names),
+        dims => bail!("{dims} dimensions, only tables with 1 to 6 are supported"),
+    }
+}
@@ -8,6 +8,16 @@ pub trait ClosestTrait<const D: usize, E: Clone> {
     /// May panic if the collection is empty
     fn closest(&self, point: [f32; D]) -> (&E, f32);
 
+    /// Returns the k closest objects from the point with their matching score, best first.
+    /// Scores of ranges are the same as `closest`, scores of points are relative to the closest point left out
+    /// so that the first one matches `closest`; every point scores 1 if none is left out.
+    fn closest_k(&self, point: [f32; D], k: usize) -> Vec<(&E, f32)>;
+
+    /// Returns the k closest objects from the point with weights summing to 1, best first.
+    /// The weight of an object falls to 0 as it gets as close as the closest object left out,
+    /// so that blended values change smoothly from one object to the next.
+    fn blend(&self, point: [f32; D], k: usize) -> Vec<(&E, f32)>;
+
     fn values(&self) -> Vec<&E>;
 
     /// Estimates the proportion space for which a non negative value is returned (ie covered space)
@@ -32,4 +42,27 @@ pub trait ClosestTrait<const D: usize, E: Clone> {
         res.divide(count as f32);
         res
     }
-}
\ No newline at end of file
+}
+
+/// Scales non negative weights so that they sum to 1, equal weights if they are all 0
+pub(crate) fn normalized<E>(weights: Vec<(E, f32)>) -> Vec<(E, f32)> {
+    let total: f32 = weights.iter().map(|(_, weight)| weight.max(0.)).sum();
+    let count = weights.len() as f32;
+    weights.into_iter()
+        .map(|(value, weight)| (value, if total > 0. { weight.max(0.)/total } else { 1./count }))
+        .collect()
+}
+
+/// Picks one of the blended objects with a probability equal to its weight, `r` being a random value in [0; 1[.
+/// Picking with a random value per position dithers the border between objects.
+/// May panic if `blend` is empty
+pub fn dither<'a, E>(blend: &[(&'a E, f32)], r: f32) -> &'a E {
+    let mut acc = 0.;
+    for (value, weight) in blend {
+        acc += weight;
+        if r < acc {
+            return value;
+        }
+    }
+    blend.last().unwrap().0
+}
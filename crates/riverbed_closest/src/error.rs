@@ -0,0 +1,97 @@
+use std::{error::Error, fmt, ops::Range};
+
+#[derive(Debug)]
+pub enum LoadErrorKind {
+    Io(std::io::Error),
+    Csv(csv::Error),
+    Json5(json5::Error),
+    /// A row doesn't have a cell for its id and each dimension
+    Width { row: usize, expected: usize, found: usize },
+    /// A cell couldn't be parsed, `expected` describes what it should contain
+    Cell { row: usize, col: usize, value: String, expected: &'static str },
+}
+
+/// Error while loading a data file.
+/// Rows and columns start at 1, CSV rows are line numbers and json5 rows are entry numbers,
+/// the id is column 1 in both formats.
+#[derive(Debug)]
+pub struct LoadError {
+    pub path: String,
+    pub kind: LoadErrorKind,
+}
+
+impl LoadError {
+    pub(crate) fn new(path: &str, kind: LoadErrorKind) -> Self {
+        LoadError { path: path.to_string(), kind }
+    }
+}
+
+impl fmt::Display for LoadErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadErrorKind::Io(err) => write!(f, "{err}"),
+            LoadErrorKind::Csv(err) => write!(f, "{err}"),
+            LoadErrorKind::Json5(err) => write!(f, "{err}"),
+            LoadErrorKind::Width { row, expected, found } =>
+                write!(f, "row {row}: expected {expected} cells, found {found}"),
+            LoadErrorKind::Cell { row, col, value, expected } =>
+                write!(f, "row {row}, column {col}: expected {expected}, got '{value}'"),
+        }
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.kind)
+    }
+}
+
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            LoadErrorKind::Io(err) => Some(err),
+            LoadErrorKind::Csv(err) => Some(err),
+            LoadErrorKind::Json5(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeIssue {
+    /// start == end, only matches the exact value
+    Empty,
+    /// start > end, never matches
+    Inverted,
+}
+
+impl RangeIssue {
+    pub fn of(range: &Range<f32>) -> Option<Self> {
+        if range.start > range.end {
+            Some(RangeIssue::Inverted)
+        } else if range.start == range.end {
+            Some(RangeIssue::Empty)
+        } else {
+            None
+        }
+    }
+}
+
+/// A range that loads fine but probably isn't what the author meant
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeWarning {
+    pub row: usize,
+    pub col: usize,
+    pub range: Range<f32>,
+    pub issue: RangeIssue,
+}
+
+impl fmt::Display for RangeWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let issue = match self.issue {
+            RangeIssue::Empty => "zero-width",
+            RangeIssue::Inverted => "inverted",
+        };
+        write!(f, "row {}, column {}: {issue} range {};{}", self.row, self.col, self.range.start, self.range.end)
+    }
+}
@@ -1,10 +1,14 @@
 mod utils;
 mod counter;
 mod closest;
+pub mod analysis;
+mod error;
+mod load;
 pub mod ranges;
 pub mod points;
 use std::fmt::Debug;
 pub use closest::*;
+pub use error::{LoadError, LoadErrorKind, RangeIssue, RangeWarning};
 use crate::counter::Counter;
 
 
@@ -28,11 +32,17 @@ pub fn print_coverage<const D: usize, E: Clone + PartialEq + Debug>(imap: impl C
 #[cfg(test)]
 mod tests {
     use std::ops::Range;
-    use crate::{print_coverage, ranges, points};
+    use crate::{analysis, dither, print_coverage, ranges, points::{self, PointIndex}, ClosestTrait, LoadErrorKind, RangeIssue};
+
+    fn temp_file(name: &str, content: &str) -> String {
+        let path = std::env::temp_dir().join(name);
+        std::fs::write(&path, content).unwrap();
+        path.to_str().unwrap().to_string()
+    }
 
     #[test]
     pub fn print_cov_ranges() {
-        let imap: Vec<([Range<f32>; 4], String)> = ranges::from_csv("benches/plants_ranges.csv").unwrap();
+        let imap: Vec<([Range<f32>; 4], String)> = ranges::from_csv("benches/plants_ranges.csv").unwrap().0;
         print_coverage(imap, 0.05);
     }
 
@@ -41,4 +51,95 @@ mod tests {
         let imap: Vec<([f32; 4], String)> = points::from_csv("benches/plants_points.csv").unwrap();
         print_coverage(imap, 0.05);
     }
+
+    #[test]
+    pub fn index_matches_scan() {
+        let imap: Vec<([f32; 4], String)> = points::from_csv("benches/plants_points.csv").unwrap();
+        let index: PointIndex<4, String> = imap.clone().into();
+        for i in 0..=200 {
+            let point = core::array::from_fn(|d| ((i * (d + 3) * 7) % 101) as f32 / 100.);
+            let (expected, expected_score) = imap.closest(point);
+            let (value, score) = index.closest(point);
+            assert!((score - expected_score).abs() < 1e-4, "at {point:?}");
+            // a score of 0 is a tie between the 2 closest points
+            if expected_score > 1e-4 {
+                assert_eq!(value, expected, "at {point:?}");
+            }
+        }
+    }
+
+    #[test]
+    pub fn blend_weights() {
+        let ranges: Vec<([Range<f32>; 4], String)> = ranges::from_csv("benches/plants_ranges.csv").unwrap().0;
+        let points: Vec<([f32; 4], String)> = points::from_csv("benches/plants_points.csv").unwrap();
+        let index: PointIndex<4, String> = points.clone().into();
+        for i in 0..=50 {
+            let point = core::array::from_fn(|d| ((i * (d + 5) * 11) % 101) as f32 / 100.);
+            assert_eq!(ranges.closest_k(point, 1)[0], ranges.closest(point));
+            assert_eq!(points.closest_k(point, 1)[0], points.closest(point));
+            for blend in [ranges.blend(point, 3), points.blend(point, 3), index.blend(point, 3)] {
+                assert_eq!(blend.len(), 3);
+                assert!((blend.iter().map(|(_, weight)| weight).sum::<f32>() - 1.).abs() < 1e-4);
+                assert!(blend.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+                assert_eq!(dither(&blend, 0.), blend[0].0);
+            }
+        }
+    }
+
+    #[test]
+    pub fn json5_matches_csv() {
+        let csv: Vec<([Range<f32>; 4], String)> = ranges::from_csv("benches/plants_ranges.csv").unwrap().0;
+        let json5: Vec<([Range<f32>; 4], String)> = ranges::from_json5("benches/plants_ranges.json5").unwrap().0;
+        assert_eq!(csv, json5);
+    }
+
+    #[test]
+    pub fn load_errors() {
+        let path = temp_file("riverbed_closest_typo.csv", "id, temp, hum\nspruce, 0.0;0.3, 0.3;0.7\nbush, 0.3;0.8, 0.0,0.5\n");
+        let err = ranges::from_csv::<2, String>(&path).unwrap_err();
+        assert!(matches!(err.kind, LoadErrorKind::Width { row: 3, expected: 3, found: 4 }), "{err}");
+        let path = temp_file("riverbed_closest_typo2.csv", "id, temp, hum\nspruce, 0.0;0.3, 0.3;0.7\nbush, 0.3;0.8, 0.0;O.5\n");
+        let err = ranges::from_csv::<2, String>(&path).unwrap_err();
+        assert!(matches!(err.kind, LoadErrorKind::Cell { row: 3, col: 3, .. }), "{err}");
+        let path = temp_file("riverbed_closest_typo.json5", "[{ id: \"bush\", values: [0.5, \"high\"] }]");
+        let err = points::from_json5::<2, String>(&path).unwrap_err();
+        assert!(matches!(err.kind, LoadErrorKind::Json5(_)), "{err}");
+        let path = temp_file("riverbed_closest_width.json5", "[{ id: \"bush\", values: [0.5] }]");
+        let err = points::from_json5::<2, String>(&path).unwrap_err();
+        assert!(matches!(err.kind, LoadErrorKind::Width { row: 1, expected: 3, found: 2 }), "{err}");
+    }
+
+    #[test]
+    pub fn range_warnings() {
+        let path = temp_file("riverbed_closest_warn.csv", "id, temp, hum\nspruce, 0.3;0.0, 0.3;0.7\nbush, 0.3;0.8, 0.5;0.5\n");
+        let (imap, warnings) = ranges::from_file_checked::<2, String>(&path).unwrap();
+        let issues: Vec<_> = warnings.iter().map(|warning| (warning.row, warning.col, warning.issue)).collect();
+        assert_eq!(issues, vec![(2, 2, RangeIssue::Inverted), (3, 3, RangeIssue::Empty)]);
+        // the zero-width range still matches its exact value without dividing by 0
+        let (value, score) = imap.closest([0.5, 0.5]);
+        assert_eq!(value, "bush");
+        assert!(score.is_finite());
+    }
+
+    #[test]
+    pub fn analysis_finds_gaps_and_shadows() {
+        let imap: Vec<([Range<f32>; 2], &str)> = vec![
+            ([0.0..0.6, 0.0..1.0], "wide"),
+            ([0.0..0.2, 0.0..1.0], "narrow"),
+            // ties go to the first entry
+            ([0.0..0.6, 0.0..1.0], "duplicate"),
+        ];
+        let report = analysis::analyze(&imap, 0.05, 0.05);
+        assert_eq!(report.samples, 21*21);
+        // nothing covers x > 0.6
+        assert_eq!(report.uncovered.len(), 1);
+        let gap = &report.uncovered[0];
+        assert!((gap.min[0] - 0.65).abs() < 1e-4);
+        assert_eq!((gap.min[1], gap.max), (0., [1., 1.]));
+        assert!((gap.share - 8./21.).abs() < 1e-4);
+        let shadowed: Vec<_> = report.shadowed.iter().map(|shadowed| (*shadowed.entry, *shadowed.by)).collect();
+        assert_eq!(shadowed, vec![("duplicate", "wide")]);
+        let covered: f32 = report.coverage.iter().map(|(_, share)| share).sum();
+        assert!((covered + gap.share - 1.).abs() < 1e-4);
+    }
 }
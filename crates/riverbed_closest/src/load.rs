@@ -0,0 +1,91 @@
+use std::{path::Path, str::FromStr};
+use serde::{de::{DeserializeOwned, IgnoredAny}, Deserialize};
+use crate::error::{LoadError, LoadErrorKind};
+
+/// A row of a data file, its cells are parsed but the id isn't, the row is kept for error messages
+pub(crate) struct Row<const D: usize, T> {
+    pub row: usize,
+    pub cells: [T; D],
+    pub id: String,
+}
+
+impl<const D: usize, T> Row<D, T> {
+    pub fn parse_id<E: FromStr>(self, path: &str) -> Result<([T; D], E), LoadError> {
+        match E::from_str(self.id.trim()) {
+            Ok(elem) => Ok((self.cells, elem)),
+            Err(_) => Err(LoadError::new(path, LoadErrorKind::Cell {
+                row: self.row, col: 1, value: self.id, expected: "a known id"
+            })),
+        }
+    }
+}
+
+/// Files are read as json5 if they have the extension, as CSV otherwise
+pub(crate) fn is_json5(path: &str) -> bool {
+    Path::new(path).extension().is_some_and(|ext| ext == "json5")
+}
+
+/// Reads a CSV file with a header, an id column then one column per dimension
+pub(crate) fn csv_rows<const D: usize, T>(
+    path: &str, expected: &'static str, parse: impl Fn(&str) -> Option<T>
+) -> Result<Vec<Row<D, T>>, LoadError> {
+    let error = |kind| LoadError::new(path, kind);
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_path(path)
+        .map_err(|err| error(LoadErrorKind::Csv(err)))?;
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|err| error(LoadErrorKind::Csv(err)))?;
+        let row = record.position().map_or(0, |pos| pos.line() as usize);
+        if record.len() != D+1 {
+            return Err(error(LoadErrorKind::Width { row, expected: D+1, found: record.len() }));
+        }
+        let mut cells = Vec::with_capacity(D);
+        for (i, cell) in record.iter().enumerate().skip(1) {
+            let Some(value) = parse(cell) else {
+                return Err(error(LoadErrorKind::Cell { row, col: i+1, value: cell.trim().to_string(), expected }));
+            };
+            cells.push(value);
+        }
+        let Ok(cells) = cells.try_into() else { unreachable!() };
+        rows.push(Row { row, cells, id: record[0].to_string() });
+    }
+    Ok(rows)
+}
+
+#[derive(Deserialize)]
+struct Entry<T> {
+    id: String,
+    values: Vec<T>,
+}
+
+/// Reads a json5 list of `{ id: "...", values: [...] }` with one value per dimension
+pub(crate) fn json5_rows<const D: usize, T: DeserializeOwned>(path: &str) -> Result<Vec<Row<D, T>>, LoadError> {
+    let error = |kind| LoadError::new(path, kind);
+    let text = std::fs::read_to_string(path).map_err(|err| error(LoadErrorKind::Io(err)))?;
+    let entries: Vec<Entry<T>> = json5::from_str(&text).map_err(|err| error(LoadErrorKind::Json5(err)))?;
+    entries.into_iter().enumerate().map(|(i, entry)| {
+        let found = entry.values.len() + 1;
+        match entry.values.try_into() {
+            Ok(cells) => Ok(Row { row: i+1, cells, id: entry.id }),
+            Err(_) => Err(error(LoadErrorKind::Width { row: i+1, expected: D+1, found })),
+        }
+    }).collect()
+}
+
+/// Names of the dimensions of a data file: its CSV header after the id,
+/// or their position in the first json5 entry as json5 files have no header
+pub(crate) fn dimension_names(path: &str) -> Result<Vec<String>, LoadError> {
+    let error = |kind| LoadError::new(path, kind);
+    if is_json5(path) {
+        let text = std::fs::read_to_string(path).map_err(|err| error(LoadErrorKind::Io(err)))?;
+        let entries: Vec<Entry<IgnoredAny>> = json5::from_str(&text).map_err(|err| error(LoadErrorKind::Json5(err)))?;
+        let dims = entries.first().map_or(0, |entry| entry.values.len());
+        Ok((1..=dims).map(|d| format!("dim{d}")).collect())
+    } else {
+        let mut reader = csv::Reader::from_path(path).map_err(|err| error(LoadErrorKind::Csv(err)))?;
+        let headers = reader.headers().map_err(|err| error(LoadErrorKind::Csv(err)))?;
+        Ok(headers.iter().skip(1).map(|name| name.trim().to_string()).collect())
+    }
+}
@@ -1,7 +1,8 @@
 use std::str::FromStr;
-use crate::ClosestTrait;
-use anyhow::{Result, bail};
+use crate::{closest::normalized, ClosestTrait};
+use crate::{error::LoadError, load::{csv_rows, is_json5, json5_rows, Row}};
 use itertools::Itertools;
+use vpsearch::{BestCandidate, MetricSpace, Tree};
 
 trait PointDistSq {
     fn dist(&self, other: &Self) -> f32;
@@ -39,21 +40,140 @@ impl<const D: usize, E: Clone> ClosestTrait<D, E> for Vec<([f32; D], E)> {
         (closest1.0, 1.-2.*closest1.1/(closest1.1 + closest2.1))
     }
 
+    fn closest_k(&self, point: [f32; D], k: usize) -> Vec<(&E, f32)> {
+        let sorted = self.iter()
+            .map(|(coords, value)| (value, coords.dist(&point)))
+            .sorted_by(|(_, d1), (_, d2)| d1.total_cmp(d2))
+            .take(k+1)
+            .collect_vec();
+        scores_k(sorted, k)
+    }
+
+    fn blend(&self, point: [f32; D], k: usize) -> Vec<(&E, f32)> {
+        normalized(self.closest_k(point, k))
+    }
+
     fn values(&self) -> Vec<&E> {
         self.iter().map(|(_, value)| value).collect_vec()
     }
 }
 
-pub fn from_csv<const D: usize, E: FromStr>(path: &str) -> Result<Vec<([f32; D], E)>> {
-    let mut res = Vec::new();
-    let mut reader = csv::Reader::from_path(path)?;
-    for record in reader.records() {
-        let record = record?;
-        let Ok(elem) = E::from_str(&record[0]) else {
-            bail!("Failed to deserialize value '{}'", &record[0]);
+/// Scores of the k first points of `sorted`, given with their squared distance, relative to the point left out
+fn scores_k<E>(mut sorted: Vec<(&E, f32)>, k: usize) -> Vec<(&E, f32)> {
+    let left_out = if sorted.len() > k { sorted.pop().map(|(_, dist)| dist) } else { None };
+    sorted.into_iter()
+        .map(|(value, dist)| (value, match left_out {
+            Some(left_out) if dist + left_out > 0. => 1.-2.*dist/(dist + left_out),
+            Some(_) => 0.,
+            None => 1.,
+        }))
+        .collect()
+}
+
+/// Point of the VP-tree, the tree needs a true metric so its distances aren't squared
+#[derive(Clone, Copy)]
+struct Point<const D: usize>([f32; D]);
+
+impl<const D: usize> MetricSpace for Point<D> {
+    type UserData = ();
+    type Distance = f32;
+
+    fn distance(&self, other: &Self, _: &()) -> f32 {
+        self.0.dist(&other.0).sqrt()
+    }
+}
+
+/// Keeps the k closest points by index, closest first
+struct KClosest {
+    k: usize,
+    found: Vec<(usize, f32)>,
+}
+
+impl<const D: usize> BestCandidate<Point<D>, ()> for KClosest {
+    type Output = Vec<(usize, f32)>;
+
+    fn consider(&mut self, _: &Point<D>, distance: f32, index: usize, _: &()) {
+        if self.found.len() == self.k && distance >= self.found[self.k-1].1 {
+            return;
+        }
+        let i = self.found.partition_point(|(_, found)| *found <= distance);
+        self.found.insert(i, (index, distance));
+        self.found.truncate(self.k);
+    }
+
+    fn distance(&self) -> f32 {
+        if self.found.len() < self.k { f32::INFINITY } else { self.found[self.k-1].1 }
+    }
+
+    fn result(self, _: &()) -> Self::Output {
+        self.found
+    }
+}
+
+/// Points indexed in a VP-tree, scores are the same as the linear scan of `Vec<([f32; D], E)>`
+/// but points at the exact same distance may be picked in a different order
+pub struct PointIndex<const D: usize, E> {
+    tree: Tree<Point<D>>,
+    points: Vec<([f32; D], E)>,
+}
+
+impl<const D: usize, E> From<Vec<([f32; D], E)>> for PointIndex<D, E> {
+    fn from(points: Vec<([f32; D], E)>) -> Self {
+        let coords = points.iter().map(|(point, _)| Point(*point)).collect_vec();
+        PointIndex { tree: Tree::new(&coords), points }
+    }
+}
+
+impl<const D: usize, E: Clone> ClosestTrait<D, E> for PointIndex<D, E> {
+    fn closest(&self, point: [f32; D]) -> (&E, f32) {
+        let found = self.tree.find_nearest_custom(&Point(point), &(), KClosest { k: 2, found: Vec::new() });
+        let (i1, dist1) = found[0];
+        let value = &self.points[i1].1;
+        let dist1 = dist1.powi(2);
+        let Some((_, dist2)) = found.get(1) else {
+            return (value, dist1);
         };
-        let intervals: [f32; D] = core::array::from_fn(|i| record[i+1].trim().parse::<f32>().unwrap());
-        res.push((intervals, elem));
+        let dist2 = dist2.powi(2);
+        (value, 1.-2.*dist1/(dist1 + dist2))
+    }
+
+    fn closest_k(&self, point: [f32; D], k: usize) -> Vec<(&E, f32)> {
+        let found = self.tree.find_nearest_custom(&Point(point), &(), KClosest { k: k+1, found: Vec::new() });
+        let sorted = found.into_iter()
+            .map(|(i, dist)| (&self.points[i].1, dist.powi(2)))
+            .collect_vec();
+        scores_k(sorted, k)
+    }
+
+    fn blend(&self, point: [f32; D], k: usize) -> Vec<(&E, f32)> {
+        normalized(self.closest_k(point, k))
+    }
+
+    fn values(&self) -> Vec<&E> {
+        self.points.values()
     }
-    Ok(res)
-}
\ No newline at end of file
+}
+
+fn load<const D: usize, E: FromStr>(path: &str, json5: bool) -> Result<Vec<([f32; D], E)>, LoadError> {
+    let rows: Vec<Row<D, f32>> = if json5 {
+        json5_rows(path)?
+    } else {
+        csv_rows(path, "a number", |cell| cell.trim().parse().ok())?
+    };
+    rows.into_iter().map(|row| row.parse_id(path)).collect()
+}
+
+/// Loads points from a json5 file if it has the extension, from a CSV file otherwise
+pub fn from_file<const D: usize, E: FromStr>(path: &str) -> Result<Vec<([f32; D], E)>, LoadError> {
+    load(path, is_json5(path))
+}
+
+/// Loads points from a CSV file with a header, an id column then a coordinate per dimension
+pub fn from_csv<const D: usize, E: FromStr>(path: &str) -> Result<Vec<([f32; D], E)>, LoadError> {
+    load(path, false)
+}
+
+/// Loads points from a json5 list of `{ id: "...", values: [...] }` with a coordinate per dimension
+pub fn from_json5<const D: usize, E: FromStr>(path: &str) -> Result<Vec<([f32; D], E)>, LoadError> {
+    load(path, true)
+}
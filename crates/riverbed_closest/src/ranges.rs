@@ -1,8 +1,14 @@
 use std::{ops::Range, str::FromStr};
 use itertools::Itertools;
-use anyhow::{Result, bail};
-use crate::{closest::ClosestTrait, utils::{range_from_str, RangesUtil}};
+use crate::{
+    closest::{normalized, ClosestTrait},
+    error::{LoadError, RangeIssue, RangeWarning},
+    load::{csv_rows, is_json5, json5_rows, Row},
+    utils::{range_from_str, RangesUtil},
+};
 
+/// Objects with a range per dimension, as loaded from data files
+pub type Ranges<const D: usize, E> = Vec<([Range<f32>; D], E)>;
 
 impl<const D: usize, E: Clone> ClosestTrait<D, E> for Vec<([Range<f32>; D], E)> {
     fn closest(&self, point: [f32; D]) -> (&E, f32) {
@@ -17,21 +23,59 @@ impl<const D: usize, E: Clone> ClosestTrait<D, E> for Vec<([Range<f32>; D], E)>
         res
     }
 
+    fn closest_k(&self, point: [f32; D], k: usize) -> Vec<(&E, f32)> {
+        self.iter()
+            .map(|(ranges, value)| (value, ranges.sign_dist(&point)))
+            .sorted_by(|(_, d1), (_, d2)| d2.total_cmp(d1))
+            .take(k)
+            .collect()
+    }
+
+    fn blend(&self, point: [f32; D], k: usize) -> Vec<(&E, f32)> {
+        let mut candidates = self.closest_k(point, k+1);
+        // weights are the margin over the best range left out
+        let left_out = if candidates.len() > k { candidates.pop().map(|(_, sign_dist)| sign_dist) } else { None };
+        normalized(candidates.into_iter()
+            .map(|(value, sign_dist)| (value, left_out.map_or(0., |left_out| sign_dist - left_out)))
+            .collect())
+    }
+
     fn values(&self) -> Vec<&E> {
         self.iter().map(|(_, value)| value).collect_vec()
     }
 }
 
-pub fn from_csv<const D: usize, E: FromStr>(path: &str) -> Result<Vec<([Range<f32>; D], E)>> {
-    let mut res = Vec::new();
-    let mut reader = csv::Reader::from_path(path)?;
-    for record in reader.records() {
-        let record = record?;
-        let Ok(elem) = E::from_str(&record[0]) else {
-            bail!("Failed to deserialize value '{}'", &record[0]);
-        };
-        let intervals: [Range<f32>; D] = core::array::from_fn(|i| range_from_str(&record[i+1]).unwrap());
-        res.push((intervals, elem));
-    }
-    Ok(res)
-}
\ No newline at end of file
+fn load<const D: usize, E: FromStr>(path: &str, json5: bool) -> Result<(Ranges<D, E>, Vec<RangeWarning>), LoadError> {
+    let rows: Vec<Row<D, Range<f32>>> = if json5 {
+        json5_rows::<D, [f32; 2]>(path)?.into_iter()
+            .map(|Row { row, cells, id }| Row { row, cells: cells.map(|[start, end]| start..end), id })
+            .collect()
+    } else {
+        csv_rows(path, "a range start;end", range_from_str)?
+    };
+    let warnings = rows.iter().flat_map(|Row { row, cells, .. }| cells.iter().enumerate()
+        .filter_map(|(i, range)| RangeIssue::of(range).map(|issue| RangeWarning {
+            row: *row, col: i+2, range: range.clone(), issue
+        }))
+    ).collect();
+    let ranges = rows.into_iter().map(|row| row.parse_id(path)).collect::<Result<_, _>>()?;
+    Ok((ranges, warnings))
+}
+
+/// Loads ranges from a json5 file if it has the extension, from a CSV file otherwise,
+/// returns the inverted and zero-width ranges alongside them
+pub fn from_file_checked<const D: usize, E: FromStr>(path: &str) -> Result<(Ranges<D, E>, Vec<RangeWarning>), LoadError> {
+    load(path, is_json5(path))
+}
+
+/// Loads ranges from a CSV file with a header, an id column then a `start;end` cell per dimension,
+/// returns the inverted and zero-width ranges alongside them
+pub fn from_csv<const D: usize, E: FromStr>(path: &str) -> Result<(Ranges<D, E>, Vec<RangeWarning>), LoadError> {
+    load(path, false)
+}
+
+/// Loads ranges from a json5 list of `{ id: "...", values: [[start, end], ...] }`,
+/// returns the inverted and zero-width ranges alongside them
+pub fn from_json5<const D: usize, E: FromStr>(path: &str) -> Result<(Ranges<D, E>, Vec<RangeWarning>), LoadError> {
+    load(path, true)
+}
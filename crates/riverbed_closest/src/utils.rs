@@ -1,5 +1,4 @@
 use std::ops::Range;
-use anyhow::{Result, anyhow};
 
 pub(crate) trait RangeUtil {
     fn sign_dist(&self, p: f32) -> f32;
@@ -7,7 +6,8 @@ pub(crate) trait RangeUtil {
 
 impl RangeUtil for Range<f32> {
     fn sign_dist(&self, p: f32) -> f32 {
-        2.*(p-self.start).min(self.end-p)/(self.end-self.start)
+        // zero-width and inverted ranges are warned about when loading, they must not divide by 0
+        2.*(p-self.start).min(self.end-p)/(self.end-self.start).max(f32::EPSILON)
     }
 }
 
@@ -24,11 +24,9 @@ impl<const D: usize> RangesUtil<D> for [Range<f32>; D] {
 }
 
 
-pub(crate) fn range_from_str(str: &str) -> Result<Range<f32>> {
-    let (start, end) = str.trim().split_once(";").ok_or(
-        anyhow!("expect format start;end, got '{}'", str)
-    )?;
-    let start = start.trim().parse::<f32>()?;
-    let end = end.trim().parse::<f32>()?;
-    Ok(start..end)
-}
\ No newline at end of file
+pub(crate) fn range_from_str(str: &str) -> Option<Range<f32>> {
+    let (start, end) = str.trim().split_once(";")?;
+    let start = start.trim().parse::<f32>().ok()?;
+    let end = end.trim().parse::<f32>().ok()?;
+    Some(start..end)
+}
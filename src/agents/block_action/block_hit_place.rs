@@ -70,7 +70,7 @@ const EDGES_LINES: [Vec3; 4] = [
 ];
 
 #[derive(Component)]
-pub struct BlockAttached(BlockPos);
+pub struct BlockAttached(pub BlockPos);
 
 #[derive(Component)]
 pub struct Renewable {
@@ -208,6 +208,10 @@ fn break_action(
                     rng.gen_range(*min..=*max)
                 }
             };
+            if quantity == 0 {
+                commands.entity(player).remove::<BlockLootAction>();
+                continue;
+            }
             let ItemHolder::Inventory(ref mut hotbar) = *hotbar else {
                 continue;
             };
@@ -239,6 +243,11 @@ fn place_block(
         if world.get_block(pos).is_targetable() {
             continue;
         }
+        if let Stack::Some(Item::Block(block), _) = hotbar.get(selected_slot.0) {
+            if block.is_sapling() && !world.get_block(pos + (0, -1, 0)).is_fertile_soil() {
+                continue;
+            }
+        }
         let block = match hotbar.get_mut(selected_slot.0).take(1) {
             Stack::Some(Item::Block(block), _) => block,
             other => {
@@ -0,0 +1,83 @@
+use crate::{
+    agents::{Action, PlayerControlled, TargetBlock},
+    items::{new_inventory, ContainerLootTable, InventoryTrait},
+    ui::{GameUiState, ItemHolder, OpenContainer},
+    world::{BlockEntities, BlockPos, VoxelWorld},
+    Block, WorldRng,
+};
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+use std::fs;
+
+pub struct ContainerActionPlugin;
+
+impl Plugin for ContainerActionPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(
+            json5::from_str::<ContainerLootTable>(
+                &fs::read_to_string("assets/data/container_loot.json5").unwrap(),
+            )
+            .unwrap(),
+        )
+        .add_systems(
+            Update,
+            open_container_menu.run_if(in_state(GameUiState::None)),
+        );
+    }
+}
+
+const CHEST_SLOTS: usize = 18;
+
+#[derive(Debug, Component)]
+pub struct Container {
+    pub name: String,
+    pub block_pos: BlockPos,
+}
+
+fn open_container_menu(
+    mut commands: Commands,
+    world: Res<VoxelWorld>,
+    block_action_query: Query<(&TargetBlock, &ActionState<Action>), With<PlayerControlled>>,
+    container_query: Query<&Container>,
+    mut block_entities: ResMut<BlockEntities>,
+    mut next_ui_state: ResMut<NextState<GameUiState>>,
+    mut container_menu: ResMut<OpenContainer>,
+    loot_table: Res<ContainerLootTable>,
+    mut world_rng: ResMut<WorldRng>,
+) {
+    for (target_block_opt, action) in block_action_query.iter() {
+        if !action.just_pressed(&Action::Modify) {
+            continue;
+        }
+        let Some(target_block) = &target_block_opt.0 else {
+            continue;
+        };
+        let container = world.get_block(target_block.pos);
+        if container != Block::Chest {
+            continue;
+        }
+        let container_ent = match block_entities.get(&target_block.pos) {
+            Some(ent) if container_query.contains(ent) => ent,
+            _ => {
+                let mut slots = new_inventory::<CHEST_SLOTS>();
+                // generated containers are filled the first time they are opened
+                if let Some(loot_table_id) = world.take_container_tag(target_block.pos) {
+                    for stack in loot_table.roll(&loot_table_id, &mut world_rng.rng) {
+                        slots.try_add(stack);
+                    }
+                }
+                let ent = commands
+                    .spawn(Container {
+                        name: container.to_string(),
+                        block_pos: target_block.pos,
+                    })
+                    .insert(ItemHolder::Inventory(slots))
+                    .id();
+                block_entities.add(&target_block.pos, ent);
+                ent
+            }
+        };
+        container_menu.0 = Some(container_ent);
+        next_ui_state.set(GameUiState::ContainerMenu);
+    }
+}
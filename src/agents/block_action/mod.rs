@@ -1,10 +1,17 @@
 mod block_hit_place;
 mod furnace_action;
+mod sapling_growth;
+mod container_action;
+mod portal_action;
 pub use furnace_action::*;
 pub use block_hit_place::*;
+pub use container_action::Container;
 use bevy::prelude::*;
 use block_hit_place::BlockHitPlacePlugin;
 use furnace_action::FurnaceActionPlugin;
+use sapling_growth::SaplingGrowthPlugin;
+use container_action::ContainerActionPlugin;
+use portal_action::PortalActionPlugin;
 
 pub struct BlockActionPlugin;
 
@@ -13,7 +20,10 @@ impl Plugin for BlockActionPlugin {
         app
             .add_plugins((
                 BlockHitPlacePlugin,
-                FurnaceActionPlugin
+                FurnaceActionPlugin,
+                SaplingGrowthPlugin,
+                ContainerActionPlugin,
+                PortalActionPlugin,
             ))
         ;
     }
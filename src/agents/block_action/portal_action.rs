@@ -0,0 +1,118 @@
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+use crate::agents::{Action, PlayerControlled, TargetBlock, Velocity};
+use crate::items::{Item, Stack};
+use crate::ui::{GameUiState, ItemHolder, SelectedHotbarSlot};
+use crate::world::{
+    find_portal, portal_destination, realm_scale, BlockPos, ColPos, PortalShape, Realm, VoxelWorld, MAX_GEN_HEIGHT,
+};
+use crate::Block;
+
+/// Distance around the arrival point where an existing portal is reused
+const PORTAL_SEARCH: i32 = 16;
+
+pub struct PortalActionPlugin;
+
+impl Plugin for PortalActionPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Update, activate_portal.run_if(in_state(GameUiState::None)))
+            .add_systems(Update, (enter_portal, arrive_through_portal).chain())
+            ;
+    }
+}
+
+/// The player is standing in a portal they arrived through, they need to step out before using it again
+#[derive(Component)]
+struct InPortal;
+
+/// The player went through a portal and waits for the other side to be generated
+#[derive(Component)]
+struct PortalArrival {
+    frame: Block,
+}
+
+fn activate_portal(
+    world: Res<VoxelWorld>,
+    mut players: Query<(&TargetBlock, &Realm, &mut ItemHolder, &ActionState<Action>), With<PlayerControlled>>,
+    selected_slot: Res<SelectedHotbarSlot>,
+) {
+    for (target_block_opt, realm, mut hotbar, action) in players.iter_mut() {
+        if !action.just_pressed(&Action::Modify) {
+            continue;
+        }
+        let Some(target_block) = &target_block_opt.0 else {
+            continue;
+        };
+        let frame = world.get_block(target_block.pos);
+        if portal_destination(frame, *realm).is_none() || hotbar.get(selected_slot.0).item() != Some(&Item::Coal) {
+            continue;
+        }
+        let Some(shape) = PortalShape::find(&world, target_block.pos + target_block.normal, frame) else {
+            continue;
+        };
+        // portals are lit with coal
+        if let Stack::Some(..) = hotbar.get_mut(selected_slot.0).take(1) {
+            shape.light(&world);
+        }
+    }
+}
+
+fn enter_portal(
+    mut commands: Commands,
+    world: Res<VoxelWorld>,
+    mut players: Query<
+        (Entity, &mut Transform, &mut Realm, &mut Velocity, Has<InPortal>),
+        (With<PlayerControlled>, Without<PortalArrival>),
+    >,
+) {
+    for (player, mut transform, mut realm, mut velocity, in_portal) in players.iter_mut() {
+        let mut pos = BlockPos::from((transform.translation, *realm));
+        if world.get_block(pos) != Block::Portal {
+            if in_portal {
+                commands.entity(player).remove::<InPortal>();
+            }
+            continue;
+        }
+        if in_portal {
+            continue;
+        }
+        while world.get_block(pos) == Block::Portal {
+            pos.y -= 1;
+        }
+        let frame = world.get_block(pos);
+        let Some(destination) = portal_destination(frame, *realm) else {
+            continue;
+        };
+        let scale = realm_scale(*realm) / realm_scale(destination);
+        // the player waits above the arrival point until it's generated,
+        // changing realm moves the load area and unloads the columns of the old realm
+        transform.translation = Vec3::new(
+            transform.translation.x * scale,
+            MAX_GEN_HEIGHT as f32,
+            transform.translation.z * scale,
+        );
+        velocity.0 = Vec3::ZERO;
+        *realm = destination;
+        commands.entity(player).insert(PortalArrival { frame });
+    }
+}
+
+fn arrive_through_portal(
+    mut commands: Commands,
+    world: Res<VoxelWorld>,
+    mut players: Query<(Entity, &mut Transform, &Realm, &mut Velocity, &PortalArrival)>,
+) {
+    for (player, mut transform, realm, mut velocity, arrival) in players.iter_mut() {
+        let target = BlockPos::from((transform.translation, *realm));
+        if !world.has_col(ColPos::from(target)) {
+            continue;
+        }
+        let portal = find_portal(&world, target, PORTAL_SEARCH).unwrap_or_else(|| {
+            PortalShape::build_at(&world, (target.x, target.z), *realm, arrival.frame).corner
+        });
+        transform.translation = Vec3::new(portal.x as f32 + 0.25, portal.y as f32, portal.z as f32 + 0.25);
+        velocity.0 = Vec3::ZERO;
+        commands.entity(player).remove::<PortalArrival>().insert(InPortal);
+    }
+}
@@ -0,0 +1,104 @@
+use std::time::{Duration, Instant};
+use bevy::prelude::*;
+use crate::agents::BlockPlaced;
+use crate::gen::{load_ranges, tree_shapes_from_json5, Tree, TreeShapes, Trees};
+use crate::world::{BlockEntities, BlockPos, BlockPos2d, VoxelWorld, MAX_GEN_HEIGHT};
+use crate::WorldRng;
+use super::BlockAttached;
+
+const GROWTH_MINUTES: u64 = 20;
+/// Delay before trying again when a sapling couldn't grow
+const RETRY_MINUTES: u64 = 5;
+
+pub struct SaplingGrowthPlugin;
+
+impl Plugin for SaplingGrowthPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(TreeGrowth {
+                conditions: load_ranges("assets/gen/trees_condition.csv"),
+                shapes: tree_shapes_from_json5("assets/gen/trees.json5").unwrap(),
+            })
+            .add_observer(on_sapling_placed)
+            .add_systems(Update, grow_saplings)
+            ;
+    }
+}
+
+#[derive(Resource)]
+struct TreeGrowth {
+    conditions: Trees,
+    shapes: TreeShapes,
+}
+
+impl TreeGrowth {
+    /// Whether the climate at the sapling's position is suitable for the tree, as in generation
+    fn suits(&self, world: &VoxelWorld, tree: Tree, pos: BlockPos) -> bool {
+        let Some(climate) = world.climate_at(BlockPos2d::from(pos)) else {
+            return false;
+        };
+        let point = [climate.temp(), climate.hum(), climate.ph(), pos.y as f32 / MAX_GEN_HEIGHT as f32];
+        self.conditions.iter()
+            .filter(|(_, cond_tree)| *cond_tree == tree)
+            .any(|(ranges, _)| ranges.iter().zip(point).all(|(range, value)| range.contains(&value)))
+    }
+}
+
+#[derive(Component)]
+pub struct Growing {
+    // TODO: same as Renewable, the world should have its own clock
+    grow_after: Instant,
+}
+
+fn growing_in(minutes: u64) -> Growing {
+    Growing { grow_after: Instant::now().checked_add(Duration::from_secs(minutes*60)).unwrap() }
+}
+
+fn on_sapling_placed(
+    block_placed: Trigger<BlockPlaced>,
+    mut commands: Commands,
+    world: Res<VoxelWorld>,
+    mut block_entities: ResMut<BlockEntities>,
+) {
+    let pos = block_placed.event().0;
+    if !world.get_block(pos).is_sapling() {
+        return;
+    }
+    let growing_entt = commands.spawn((growing_in(GROWTH_MINUTES), BlockAttached(pos))).id();
+    block_entities.add(&pos, growing_entt);
+}
+
+fn grow_saplings(
+    mut commands: Commands,
+    world: Res<VoxelWorld>,
+    tree_growth: Res<TreeGrowth>,
+    world_rng: Res<WorldRng>,
+    mut block_entities: ResMut<BlockEntities>,
+    mut saplings: Query<(Entity, &mut Growing, &BlockAttached)>,
+) {
+    let now = Instant::now();
+    for (entity, mut growing, pos) in saplings.iter_mut() {
+        if now < growing.grow_after {
+            continue;
+        }
+        let pos = pos.0;
+        let Some(tree) = Tree::from_sapling(world.get_block(pos)) else {
+            // the sapling is gone
+            commands.entity(entity).despawn();
+            block_entities.remove(&pos);
+            continue;
+        };
+        let seed = world_rng.seed as i32;
+        // same size variation as generated trees
+        let dist = ((pos.prng(seed) >> 5) & 0b11) as f32 / 10.;
+        let soil = pos + (0, -1, 0);
+        let fits = tree_growth.shapes.get(&tree).is_some_and(|shape| shape.has_room(&world, soil, dist));
+        if !fits || !tree_growth.suits(&world, tree, pos) {
+            *growing = growing_in(RETRY_MINUTES);
+            continue;
+        }
+        tree.grow(&tree_growth.shapes, &world, soil, seed, dist);
+        commands.entity(entity).despawn();
+        block_entities.remove(&pos);
+    }
+}
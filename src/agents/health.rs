@@ -0,0 +1,55 @@
+use bevy::prelude::*;
+use crate::world::{BlockPos, Realm, VoxelWorld};
+use super::{PlayerControlled, Velocity, AABB, SPAWN};
+
+pub struct HealthPlugin;
+
+impl Plugin for HealthPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Update, (hazard_damage, respawn_player).chain())
+            ;
+    }
+}
+
+#[derive(Component)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Health { current: max, max }
+    }
+}
+
+/// Entities lose health in the most damaging block their AABB is in
+fn hazard_damage(
+    world: Res<VoxelWorld>,
+    time: Res<Time>,
+    mut query: Query<(&Transform, &Realm, &AABB, &mut Health)>,
+) {
+    for (transform, realm, aabb, mut health) in query.iter_mut() {
+        let feet = BlockPos::from((transform.translation, *realm));
+        let damage = (0..=aabb.0.y.floor() as i32)
+            .map(|dy| world.get_block_safe(feet + (0, dy, 0)).damage())
+            .fold(0., f32::max);
+        health.current = (health.current - damage * time.delta_secs()).max(0.);
+    }
+}
+
+/// A player out of health is sent back to the spawn of the Overworld
+fn respawn_player(mut query: Query<(&mut Transform, &mut Realm, &mut Velocity, &mut Health), With<PlayerControlled>>) {
+    for (mut transform, mut realm, mut velocity, mut health) in query.iter_mut() {
+        if health.current > 0. {
+            continue;
+        }
+        transform.translation = SPAWN;
+        velocity.0 = Vec3::ZERO;
+        if *realm != Realm::Overworld {
+            *realm = Realm::Overworld;
+        }
+        health.current = health.max;
+    }
+}
@@ -2,6 +2,8 @@ mod player;
 mod movement;
 mod block_action;
 mod key_binds;
+mod health;
 pub use player::*;
 pub use movement::*;
-pub use block_action::*;
\ No newline at end of file
+pub use block_action::*;
+pub use health::*;
\ No newline at end of file
@@ -6,11 +6,12 @@ use bevy::{
     prelude::*,
 };
 use leafwing_input_manager::prelude::*;
-use super::{block_action::BlockActionPlugin, key_binds::KeyBinds, Crouching, FreeFly, Speed, SteppingOn, Walking};
+use super::{block_action::BlockActionPlugin, health::{Health, HealthPlugin}, key_binds::KeyBinds, Crouching, FreeFly, Speed, SteppingOn, Walking};
 
 const WALK_SPEED: f32 = 7.;
+const PLAYER_HEALTH: f32 = 20.;
 const FREE_FLY_X_SPEED: f32 = 150.;
-const SPAWN: Vec3 = Vec3 { x: 540., y: 500., z: 130.};
+pub const SPAWN: Vec3 = Vec3 { x: 540., y: 500., z: 130.};
 pub const HOTBAR_SLOTS: usize = 8;
 
 pub struct PlayerPlugin;
@@ -23,6 +24,7 @@ impl Plugin for PlayerPlugin {
         app
             .insert_resource(confy::load_path::<KeyBinds>("key_bindings.toml").unwrap())
             .add_plugins(BlockActionPlugin)
+            .add_plugins(HealthPlugin)
             .add_plugins(InputManagerPlugin::<Dir>::default())
             .add_plugins(InputManagerPlugin::<Action>::default())
             .add_plugins(InputManagerPlugin::<DevCommand>::default())
@@ -101,6 +103,7 @@ pub fn spawn_player(mut commands: Commands, key_binds: Res<KeyBinds>) {
             Walking,
             SteppingOn(Block::Air),
             Crouching(false),
+            Health::new(PLAYER_HEALTH),
         ))
         .insert(SpatialListener::new(0.3))
         .insert((FootstepCD(0.), BlockSoundCD(0.)))
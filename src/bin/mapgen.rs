@@ -0,0 +1,211 @@
+//! Generates a rectangle of columns without a window and writes top-down maps and statistics.
+//!
+//! Usage: mapgen [--preset NAME] [--gen NAME] [--seed N] [--realm REALM] [--x X] [--z Z] [--size N] [--out DIR]
+//! --x, --z are the column coordinates of the top left corner, --size the side of the rectangle in columns.
+use std::{collections::HashMap, fs, hash::{DefaultHasher, Hash, Hasher}, path::PathBuf, str::FromStr};
+use anyhow::{bail, Context, Result};
+use image::{Rgb, RgbImage};
+use itertools::iproduct;
+use riverbed::{
+    gen::{Biome, GeneratorRegistry, WorldPreset},
+    world::{BlockPos2d, ColPos, Realm, VoxelWorld, CHUNK_S1, CHUNK_S1I, MAX_GEN_HEIGHT},
+    Block, BlockFamily,
+};
+
+struct Args {
+    preset: String,
+    gen: Option<String>,
+    seed: Option<u64>,
+    realm: Realm,
+    x: i32,
+    z: i32,
+    size: i32,
+    out: PathBuf,
+}
+
+fn parse_args() -> Result<Args> {
+    let mut args = Args {
+        preset: "default".to_string(),
+        gen: None,
+        seed: None,
+        realm: Realm::Overworld,
+        x: -4,
+        z: -4,
+        size: 8,
+        out: PathBuf::from("map_output"),
+    };
+    let mut argv = std::env::args().skip(1);
+    while let Some(flag) = argv.next() {
+        let value = argv.next().with_context(|| format!("missing value for {flag}"))?;
+        match flag.as_str() {
+            "--preset" => args.preset = value,
+            "--gen" => args.gen = Some(value),
+            "--seed" => args.seed = Some(value.parse()?),
+            "--realm" => args.realm = Realm::from_str(&value)?,
+            "--x" => args.x = value.parse()?,
+            "--z" => args.z = value.parse()?,
+            "--size" => args.size = value.parse()?,
+            "--out" => args.out = PathBuf::from(value),
+            _ => bail!("unknown argument {flag}"),
+        }
+    }
+    Ok(args)
+}
+
+fn hashed_color<T: Hash>(value: T) -> Rgb<u8> {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    let [r, g, b, ..] = hasher.finish().to_le_bytes();
+    Rgb([r, g, b])
+}
+
+fn block_color(block: Block) -> Rgb<u8> {
+    let families = block.families();
+    if families.contains(&BlockFamily::Leaves) {
+        return Rgb([40, 110, 40]);
+    }
+    if families.contains(&BlockFamily::Log) {
+        return Rgb([100, 70, 40]);
+    }
+    match block {
+        Block::SeaBlock => Rgb([40, 80, 200]),
+        Block::GrassBlock => Rgb([90, 170, 60]),
+        Block::Dirt | Block::CoarseDirt => Rgb([120, 85, 55]),
+        Block::Podzol => Rgb([90, 60, 30]),
+        Block::Mud => Rgb([70, 55, 45]),
+        Block::Sand => Rgb([220, 205, 140]),
+        Block::Gravel => Rgb([135, 125, 120]),
+        Block::Snow => Rgb([240, 240, 250]),
+        Block::Ice => Rgb([170, 210, 250]),
+        Block::Cobblestone => Rgb([120, 120, 120]),
+        Block::Granite => Rgb([150, 130, 125]),
+        Block::Air => Rgb([0, 0, 0]),
+        _ => hashed_color(block),
+    }
+}
+
+fn biome_color(biome: Biome) -> Rgb<u8> {
+    match biome {
+        Biome::Desert => Rgb([230, 210, 130]),
+        Biome::Savanna => Rgb([190, 180, 80]),
+        Biome::Jungle => Rgb([20, 120, 30]),
+        Biome::Grassland => Rgb([120, 200, 80]),
+        Biome::TemperateForest => Rgb([50, 150, 60]),
+        Biome::Marsh => Rgb([80, 110, 80]),
+        Biome::Taiga => Rgb([60, 100, 90]),
+        Biome::Tundra => Rgb([200, 210, 220]),
+    }
+}
+
+/// Blue for 0, red for 1
+fn gradient(value: f32) -> Rgb<u8> {
+    let value = value.clamp(0., 1.);
+    Rgb([(value * 255.) as u8, 40, ((1. - value) * 255.) as u8])
+}
+
+fn is_tree(block: Block) -> bool {
+    let families = block.families();
+    families.contains(&BlockFamily::Leaves)
+        || families.contains(&BlockFamily::Log)
+        || matches!(block, Block::Cactus | Block::Bamboo)
+}
+
+fn main() -> Result<()> {
+    let args = parse_args()?;
+    let registry = GeneratorRegistry::new();
+    let mut preset = WorldPreset::from_json5(format!("assets/gen/presets/{}.json5", args.preset))?;
+    if let Some(seed) = args.seed {
+        preset.seed = Some(seed);
+    }
+    preset.validate(&registry)?;
+    let gen_name = args.gen.clone()
+        .or_else(|| preset.generators.get(&args.realm).cloned())
+        .context("no generator for this realm")?;
+    let gen = registry.create(&gen_name, &preset).with_context(|| format!("unknown generator '{gen_name}'"))?;
+    let world = VoxelWorld::new();
+    let cols: Vec<ColPos> = iproduct!(args.x..args.x + args.size, args.z..args.z + args.size)
+        .map(|(x, z)| ColPos { x, z, realm: args.realm })
+        .collect();
+    for col in cols.iter() {
+        gen.gen(&world, *col);
+    }
+    for col in cols.iter() {
+        gen.gen_underground(&world, *col);
+    }
+
+    let side = (args.size * CHUNK_S1I) as u32;
+    let mut height_map = RgbImage::new(side, side);
+    let mut top_map = RgbImage::new(side, side);
+    let mut temp_map = RgbImage::new(side, side);
+    let mut hum_map = RgbImage::new(side, side);
+    let mut biome_map = RgbImage::new(side, side);
+    let mut tree_map = RgbImage::new(side, side);
+    let mut top_histogram: HashMap<Block, usize> = HashMap::new();
+    let mut biome_coverage: HashMap<Biome, usize> = HashMap::new();
+    for (px, pz) in iproduct!(0..side, 0..side) {
+        let pos = BlockPos2d {
+            x: args.x * CHUNK_S1I + px as i32,
+            z: args.z * CHUNK_S1I + pz as i32,
+            realm: args.realm,
+        };
+        let (block, y) = world.top_block(pos);
+        let shade = (y.max(0) as f32 / MAX_GEN_HEIGHT as f32 * 255.).min(255.) as u8;
+        height_map.put_pixel(px, pz, Rgb([shade, shade, shade]));
+        top_map.put_pixel(px, pz, block_color(block));
+        if is_tree(block) {
+            tree_map.put_pixel(px, pz, block_color(block));
+        }
+        *top_histogram.entry(block).or_default() += 1;
+        if let Some(climate) = world.climate_at(pos) {
+            temp_map.put_pixel(px, pz, gradient(climate.temp()));
+            hum_map.put_pixel(px, pz, gradient(climate.hum()));
+            biome_map.put_pixel(px, pz, biome_color(climate.biome));
+            *biome_coverage.entry(climate.biome).or_default() += 1;
+        }
+    }
+    fs::create_dir_all(&args.out)?;
+    for (name, map) in [
+        ("height", &height_map),
+        ("top_block", &top_map),
+        ("temperature", &temp_map),
+        ("humidity", &hum_map),
+        ("biome", &biome_map),
+        ("trees", &tree_map),
+    ] {
+        map.save(args.out.join(format!("{name}.png")))?;
+    }
+
+    let mut histogram: HashMap<Block, usize> = HashMap::new();
+    for chunk in world.chunks.iter() {
+        for (x, y, z) in iproduct!(0..CHUNK_S1, 0..CHUNK_S1, 0..CHUNK_S1) {
+            *histogram.entry(*chunk.value().get((x, y, z))).or_default() += 1;
+        }
+    }
+    let columns = (side * side) as f32;
+    let volume = histogram.values().sum::<usize>() as f32;
+    let mut stats = format!(
+        "generator: {gen_name}, preset: {}, seed: {}, realm: {:?}\ncolumns: x {}..{}, z {}..{}\n",
+        args.preset, preset.seed(), args.realm, args.x, args.x + args.size, args.z, args.z + args.size
+    );
+    stats += "\n# blocks\n";
+    let mut histogram: Vec<_> = histogram.into_iter().collect();
+    histogram.sort_by(|(b1, c1), (b2, c2)| c2.cmp(c1).then(b1.to_string().cmp(&b2.to_string())));
+    for (block, count) in histogram {
+        stats += &format!("{block}: {count} ({:.3}%)\n", count as f32 / volume * 100.);
+    }
+    stats += "\n# top blocks\n";
+    let mut top_histogram: Vec<_> = top_histogram.into_iter().collect();
+    top_histogram.sort_by(|(b1, c1), (b2, c2)| c2.cmp(c1).then(b1.to_string().cmp(&b2.to_string())));
+    for (block, count) in top_histogram {
+        stats += &format!("{block}: {count} ({:.2}%)\n", count as f32 / columns * 100.);
+    }
+    stats += "\n# biome coverage\n";
+    let mut biome_coverage: Vec<_> = biome_coverage.into_iter().collect();
+    biome_coverage.sort_by(|(b1, c1), (b2, c2)| c2.cmp(c1).then(format!("{b1:?}").cmp(&format!("{b2:?}"))));
+    for (biome, count) in biome_coverage {
+        stats += &format!("{biome:?}: {:.2}%\n", count as f32 / columns * 100.);
+    }
+    fs::write(args.out.join("stats.txt"), &stats)?;
+    print!("{stats}");
+    Ok(())
+}
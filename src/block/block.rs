@@ -1,54 +1,30 @@
+use std::str::FromStr;
 use crate::{Block, BlockFamily};
 
 impl Block {
-    pub fn friction(&self) -> f32 {
-        match self {
-            Block::Air => 0.05,
-            Block::Ice => 0.05,
-            _ => 1.
-        }
-    }
-
-    pub fn slowing(&self) -> f32 {
-        match self {
-            Block::Mud => 0.8,
-            _ => 1.
-        }
+    pub fn is_foliage(&self) -> bool {
+        self.families().contains(&BlockFamily::Leaves)
     }
 
-    pub fn is_traversable(&self) -> bool {
-        match self {
-            Block::Air | Block::SeaBlock => true,
-            _ => false,
-        }
+    pub fn is_plant(&self) -> bool {
+        let families = self.families();
+        families.contains(&BlockFamily::Plant) || families.contains(&BlockFamily::Sapling)
     }
 
-    pub fn is_targetable(&self) -> bool {
-        match self {
-            Block::Air | Block::SeaBlock => false,
-            _ => true
-        }
-    }
-    
-    pub fn is_opaque(&self) -> bool {
-        if self.is_foliage() {
-            return false;
-        }
-        match self {
-            Block::Glass | Block::SeaBlock | Block::Air | Block::Campfire => false,
-            _ => true
-        }
+    pub fn is_sapling(&self) -> bool {
+        self.families().contains(&BlockFamily::Sapling)
     }
 
-    pub fn is_foliage(&self) -> bool {
-        self.families().contains(&BlockFamily::Leaves)
+    /// The sapling dropped by leaves of the same wood, if there is one
+    pub fn sapling(&self) -> Option<Block> {
+        let wood = self.to_string().strip_suffix("Leaves")?.to_string();
+        Block::from_str(&format!("{wood}Sapling")).ok()
     }
 
-    pub fn is_fertile_soil(&self) -> bool {
+    pub fn grows_on_water(&self) -> bool {
         match self {
-            Block::GrassBlock | Block::Podzol | Block::Snow
-                => true,
+            Block::Lily => true,
             _ => false
         }
     }
-}
+}
\ No newline at end of file
@@ -0,0 +1,251 @@
+use crate::{Block, BlockFamily, gen::Soils};
+use crate::world::{
+    BlockPos, BlockPos2d, ChunkPos, Climate, ColClimate, ColPos, TrackedChunk, VoxelWorld, CHUNK_S1, CHUNK_S1I,
+};
+use riverbed_closest::ClosestTrait;
+use bevy::prelude::info_span;
+use itertools::iproduct;
+use noise_algebra::NoiseSource;
+
+use super::generator::WorldGenerator;
+use super::biome::Biome;
+use super::growables::{tree_rank, tree_shapes_from_json5, TreeShapes};
+use super::tree::Tree;
+use super::preset::WorldPreset;
+use super::{load_ranges, pos_to_range};
+/// Island centers are spread between these heights
+const ISLAND_LOW: i32 = 140;
+const ISLAND_HIGH: i32 = 260;
+/// Height of the islands above their center, at full strength
+const ISLAND_TOP: f32 = 10.;
+/// Depth of the islands below their center, at full strength
+const ISLAND_DEPTH: f32 = 56.;
+/// Maximum contribution of the 3D noise to the island density
+const ISLAND_NOISE: f32 = 0.4;
+/// Dirt blocks under the soil of the islands, the rest is limestone
+const DIRT_DEPTH: i32 = 3;
+const SPRINGS: usize = 2;
+/// Longest distance a stream flows on an island before dropping off it
+const STREAM_LEN: usize = 24;
+/// A drop at least this high in a stream makes a waterfall
+const FALL_H: i32 = 4;
+/// Longest waterfall, falls into the void end in mid-air instead of reaching the bottom of the world
+const FALL_LEN: i32 = 64;
+const TREE_SPACING: usize = 12;
+const AETHER_SALT: i32 = 0xae7e;
+const SPRING_SALT: i32 = 0x5971;
+
+/// Floating islands over the void, with streams falling off their edges
+pub struct AetherGen {
+    soils: Soils,
+    tree_shapes: TreeShapes,
+    /// Species growing on the islands, sorted so that picking one at random is deterministic
+    species: Vec<Tree>,
+    seed: i32,
+}
+
+impl AetherGen {
+    pub fn new(preset: &WorldPreset) -> Self {
+        // only the soil family makes island tops, no ice
+        let soils: Soils = load_ranges("assets/gen/soils_condition.csv");
+        let soils = soils.into_iter()
+            .filter(|(_, block)| block.families().contains(&BlockFamily::Soil))
+            .collect();
+        let tree_shapes = tree_shapes_from_json5("assets/gen/aether_trees.json5").unwrap();
+        let mut species: Vec<Tree> = tree_shapes.keys().copied().collect();
+        species.sort_by_key(|tree| *tree as u8);
+        AetherGen {
+            soils,
+            tree_shapes,
+            species,
+            seed: preset.seed() as i32 ^ AETHER_SALT,
+        }
+    }
+
+    /// Island density at `rel` blocks above the island center, positive inside the island
+    fn density(strength: f32, rel: f32, noise: f32) -> f32 {
+        let profile = if rel >= 0. { rel / ISLAND_TOP } else { -rel / (ISLAND_DEPTH * strength) };
+        strength.sqrt() - profile + (noise - 0.5) * 2. * ISLAND_NOISE
+    }
+
+    /// Heights of the column between which the island density can be positive
+    fn bounds(strength: f32, center: i32) -> (i32, i32) {
+        let reach = strength.sqrt() + ISLAND_NOISE;
+        (center - (ISLAND_DEPTH * strength * reach) as i32, center + (ISLAND_TOP * reach) as i32)
+    }
+
+    /// Turns the top of the islands into streams running from springs towards the island edges,
+    /// where they fall until they hit the ground, pass below `low`, the bottom of the lowest island of the column,
+    /// or have fallen `FALL_LEN` blocks. Streams leaving the column or going uphill dry up.
+    fn gen_waterfalls(&self, world: &VoxelWorld, col: ColPos, surface: &[[Option<i32>; CHUNK_S1]; CHUNK_S1], low: i32) {
+        const DIRS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+        let block_pos = |(dx, dz): (i32, i32), y: i32| BlockPos {
+            x: col.x * CHUNK_S1I + dx,
+            y,
+            z: col.z * CHUNK_S1I + dz,
+            realm: col.realm,
+        };
+        for spring in 0..SPRINGS {
+            let rng = <BlockPos2d>::from((col, (spring, 0))).prng(self.seed ^ SPRING_SALT);
+            let (mut dx, mut dz) = ((rng % CHUNK_S1) as i32, ((rng >> 6) % CHUNK_S1) as i32);
+            let (step_x, step_z) = DIRS[(rng >> 12) & 0b11];
+            let Some(mut y) = surface[dx as usize][dz as usize] else {
+                continue;
+            };
+            let mut stream = vec![(dx, dz, y)];
+            let fall = (0..STREAM_LEN).find_map(|_| {
+                let (nx, nz) = (dx + step_x, dz + step_z);
+                if !(0..CHUNK_S1I).contains(&nx) || !(0..CHUNK_S1I).contains(&nz) {
+                    return Some(None);
+                }
+                match surface[nx as usize][nz as usize] {
+                    Some(ny) if ny > y => Some(None),
+                    Some(ny) if ny > y - FALL_H => {
+                        (dx, dz, y) = (nx, nz, ny);
+                        stream.push((dx, dz, y));
+                        None
+                    }
+                    _ => Some(Some((nx, nz, y))),
+                }
+            });
+            let Some(Some((fx, fz, top))) = fall else {
+                continue;
+            };
+            for (dx, dz, y) in stream {
+                world.set_block(block_pos((dx, dz), y), Block::SeaBlock);
+            }
+            // leaves of the trees in the way are washed away like the terrain would replace them
+            for y in ((top - FALL_LEN).max(low)..=top).rev() {
+                let pos = block_pos((fx, fz), y);
+                let block = world.get_block(pos);
+                if block != Block::Air && tree_rank(block).is_none() {
+                    break;
+                }
+                world.set_block(pos, Block::SeaBlock);
+            }
+        }
+    }
+}
+
+impl WorldGenerator for AetherGen {
+    fn gen(&self, world: &VoxelWorld, col: ColPos) {
+        // an empty chunk so that the column counts as loaded even without islands
+        let chunk_pos = ChunkPos { x: col.x, y: 0, z: col.z, realm: col.realm };
+        world.chunks.entry(chunk_pos).or_insert_with(TrackedChunk::new);
+        let gen_span = info_span!("aether noise gen", name = "aether noise gen").entered();
+        let [z_range, x_range] = pos_to_range(col);
+        let mut n = NoiseSource::new([z_range.clone(), x_range.clone()], self.seed, 1);
+        let islands = (n.simplex(1.5) + n.simplex(6.) * 0.3 + n.simplex(24.) * 0.1)
+            .normalize()
+            .threshold(0.6);
+        let centers = (n.simplex(0.5) + n.simplex(3.) * 0.2)
+            .normalize()
+            .map(|c| ISLAND_LOW + (c * (ISLAND_HIGH - ISLAND_LOW) as f32) as i32);
+        let ts = (n.simplex(0.3) + n.simplex(4.) * 0.1).normalize();
+        let hs = (n.simplex(0.4) + n.simplex(5.) * 0.1).normalize();
+        let trees = (n.simplex(2.) + n.simplex(8.) * 0.3).normalize();
+        gen_span.exit();
+        let mut climate = ColClimate::new();
+        let mut soils = [[Block::Dirt; CHUNK_S1]; CHUNK_S1];
+        for (dx, dz) in iproduct!(0..CHUNK_S1, 0..CHUNK_S1) {
+            // the Aether is mild, soils go from snow to coarse dirt
+            let (t, h) = (0.2 + ts[[dx, dz]] * 0.6, hs[[dx, dz]]);
+            climate.set((dx, dz), Climate::new(t, h, 0.5, Biome::Grassland));
+            let (soil, score) = self.soils.closest([t, h]);
+            if score >= 0. {
+                soils[dx][dz] = *soil;
+            }
+        }
+        world.set_climate(col, climate);
+        let columns: Vec<_> = iproduct!(0..CHUNK_S1, 0..CHUNK_S1)
+            .filter(|(dx, dz)| islands[[*dx, *dz]] > 0.)
+            .map(|(dx, dz)| (dx, dz, Self::bounds(islands[[dx, dz]], centers[[dx, dz]])))
+            .collect();
+        let (Some(low), Some(high)) = (
+            columns.iter().map(|(_, _, (low, _))| *low).min(),
+            columns.iter().map(|(_, _, (_, high))| *high).max(),
+        ) else {
+            return;
+        };
+        let gen_span = info_span!("island noise gen", name = "island noise gen").entered();
+        let mut n = NoiseSource::new([z_range, low..=high, x_range], self.seed, 1);
+        let density = (n.simplex(3.) + n.simplex(10.) * 0.3 + n.simplex(30.) * 0.1).normalize();
+        gen_span.exit();
+        let fill_span = info_span!("island filling", name = "island filling").entered();
+        let mut surface = [[None; CHUNK_S1]; CHUNK_S1];
+        // solid blocks right above the current one, chunks are filled from the top to lay the soil first
+        let mut depth = [[0; CHUNK_S1]; CHUNK_S1];
+        for cy in (low.div_euclid(CHUNK_S1I)..=high.div_euclid(CHUNK_S1I)).rev() {
+            let chunk_pos = ChunkPos { x: col.x, y: cy, z: col.z, realm: col.realm };
+            let mut chunk = world.chunks.entry(chunk_pos).or_insert_with(TrackedChunk::new);
+            for &(dx, dz, (col_low, col_high)) in columns.iter() {
+                for dy in (0..CHUNK_S1).rev() {
+                    let y = cy * CHUNK_S1I + dy as i32;
+                    if y < col_low || y > col_high {
+                        continue;
+                    }
+                    let rel = (y - centers[[dx, dz]]) as f32;
+                    if Self::density(islands[[dx, dz]], rel, density[[dx, (y - low) as usize, dz]]) <= 0. {
+                        depth[dx][dz] = 0;
+                        continue;
+                    }
+                    let block = match depth[dx][dz] {
+                        0 => soils[dx][dz],
+                        d if d <= DIRT_DEPTH => Block::Dirt,
+                        _ => Block::Limestone,
+                    };
+                    chunk.set((dx, dy, dz), block);
+                    depth[dx][dz] += 1;
+                    surface[dx][dz] = surface[dx][dz].or(Some(y));
+                }
+            }
+        }
+        fill_span.exit();
+        let water_span = info_span!("waterfall gen", name = "waterfall gen").entered();
+        self.gen_waterfalls(world, col, &surface, low);
+        water_span.exit();
+        let tree_span = info_span!("tree gen", name = "tree gen").entered();
+        for (gx, gz) in iproduct!((0..CHUNK_S1).step_by(TREE_SPACING), (0..CHUNK_S1).step_by(TREE_SPACING)) {
+            let rng = <BlockPos2d>::from((col, (gx, gz))).prng(self.seed);
+            let dx = (gx + rng % TREE_SPACING).min(CHUNK_S1 - 1);
+            let dz = (gz + (rng >> 4) % TREE_SPACING).min(CHUNK_S1 - 1);
+            let (Some(y), true) = (surface[dx][dz], trees[[dx, dz]] > 0.5) else {
+                continue;
+            };
+            if self.species.is_empty() {
+                break;
+            }
+            let tree = self.species[(rng >> 8) % self.species.len()];
+            let dist = ((rng >> 12) & 0b11) as f32 / 10.;
+            tree.grow(&self.tree_shapes, world, BlockPos::from((col, (dx, y, dz))), self.seed, dist);
+        }
+        tree_span.exit();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::iproduct;
+    use crate::world::{ChunkPos, ColPos, Realm, VoxelWorld, Y_CHUNKS};
+    use crate::gen::{generator::WorldGenerator, preset::WorldPreset};
+    use super::AetherGen;
+
+    fn encoded(world: &VoxelWorld, col: ColPos) -> Vec<Option<Vec<u8>>> {
+        (0..Y_CHUNKS as i32)
+            .map(|y| world.chunks.get(&ChunkPos { x: col.x, y, z: col.z, realm: col.realm }).map(|chunk| chunk.encode()))
+            .collect()
+    }
+
+    #[test]
+    fn islands_are_deterministic() {
+        let gen = AetherGen::new(&WorldPreset { seed: Some(7), ..Default::default() });
+        assert_eq!(gen.species.len(), 3);
+        let cols: Vec<_> = iproduct!(-1..=1, -1..=1).map(|(x, z)| ColPos { x, z, realm: Realm::Aether }).collect();
+        let (world, reversed) = (VoxelWorld::new(), VoxelWorld::new());
+        cols.iter().for_each(|col| gen.gen(&world, *col));
+        cols.iter().rev().for_each(|col| gen.gen(&reversed, *col));
+        for col in cols {
+            assert_eq!(encoded(&world, col), encoded(&reversed, col), "{col:?}");
+        }
+    }
+}
@@ -1,11 +1,19 @@
+use std::ops::RangeInclusive;
 use noise_algebra::{NoiseSource, Signal2d};
-use serde::Deserialize;
+use riverbed_closest::points::PointIndex;
+use serde::{Deserialize, Serialize};
 use strum_macros::EnumString;
 
 use crate::Block;
+use crate::world::MAX_GEN_HEIGHT;
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize)]
+/// Number of closest biomes blended at each column, their height modifiers are interpolated
+/// and their layers dithered so that biome borders aren't straight cuts
+pub const BIOMES_BLENDED: usize = 2;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
 #[derive(EnumString)]
+#[strum(serialize_all = "snake_case")]
 pub enum Biome {
     Desert,
     Savanna,
@@ -17,6 +25,8 @@ pub enum Biome {
     Tundra
 }
 
+pub type Biomes = PointIndex<2, Biome>;
+
 pub enum Height {
     Const(i32),
     Noise(Signal2d)
@@ -24,19 +34,29 @@ pub enum Height {
 
 impl From<i32> for Height {
     fn from(value: i32) -> Self {
-        todo!()
+        Height::Const(value)
     }
 }
 
 impl From<Signal2d> for Height {
     fn from(value: Signal2d) -> Self {
-        todo!()
+        Height::Noise(value)
+    }
+}
+
+impl Height {
+    /// Height in blocks at a position in the column, noise values are expressed as a fraction of MAX_GEN_HEIGHT
+    pub fn at(&self, (dx, dz): (usize, usize)) -> i32 {
+        match self {
+            Height::Const(value) => *value,
+            Height::Noise(signal) => (signal[[dx, dz]] * MAX_GEN_HEIGHT as f32) as i32
+        }
     }
 }
 
 pub struct Layer {
-    height: Height,
-    block: Block
+    pub height: Height,
+    pub block: Block
 }
 
 impl Layer {
@@ -45,6 +65,7 @@ impl Layer {
     }
 }
 
+/// Biome specific generation rules, layers are stacked on top of the terrain (after height_mod), first layer at the bottom
 pub struct BiomeGen {
     pub height_mod: Height,
     pub layers: Vec<Layer>
@@ -57,7 +78,30 @@ impl Biome {
                 height_mod: Height::Const(0),
                 layers: vec![Layer::new(n.ridge(3.)*0.1, Block::Sand)]
             }),
+            Biome::Jungle => Some(BiomeGen {
+                height_mod: Height::Noise(n.simplex(4.).normalize()*0.03),
+                layers: Vec::new()
+            }),
+            Biome::Marsh => Some(BiomeGen {
+                height_mod: Height::Const(-2),
+                layers: vec![Layer::new(1, Block::Mud)]
+            }),
+            Biome::Taiga => Some(BiomeGen {
+                height_mod: Height::Const(0),
+                layers: vec![Layer::new(1, Block::Podzol)]
+            }),
+            Biome::Tundra => Some(BiomeGen {
+                height_mod: Height::Const(0),
+                layers: vec![Layer::new(n.simplex(8.).normalize()*0.005, Block::Snow)]
+            }),
             _ => None
         }
     }
-}
\ No newline at end of file
+
+    /// Biome generation rules for a column, each biome gets its own noise source so that
+    /// its rules don't depend on which other biomes are present in the column
+    pub fn col_gen(&self, range: [RangeInclusive<i32>; 2], seed: i32) -> Option<BiomeGen> {
+        let mut n = NoiseSource::new(range, seed.wrapping_add(1 + *self as i32), 1);
+        self.custom_gen(&mut n)
+    }
+}
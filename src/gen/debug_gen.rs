@@ -3,19 +3,19 @@ use crate::world::{
     unchunked, VoxelWorld, ColPos
 };
 use crate::{Block, gen::Soils};
-use riverbed_closest::{points, ranges, ClosestTrait};
+use super::{generator::WorldGenerator, load_ranges};
+use riverbed_closest::{points, ClosestTrait};
 use itertools::iproduct;
-use std::{collections::HashMap, path::Path};
+use std::path::Path;
 
 pub struct DebugGen {
     seed: u32,
-    config: HashMap<String, f32>,
     soils: Soils,
 }
 
 impl Clone for DebugGen {
     fn clone(&self) -> Self {
-        DebugGen::new(self.seed, self.config.clone())
+        DebugGen::new(self.seed)
     }
 }
 
@@ -25,18 +25,19 @@ fn values(x: i32, z: i32) -> (f32, f32, f32) {
 }
 
 impl DebugGen {
-    pub fn new(seed: u32, config: std::collections::HashMap<String, f32>) -> Self
+    pub fn new(seed: u32) -> Self
     where
         Self: Sized + Clone,
     {
         DebugGen {
             seed,
-            config,
-            soils: ranges::from_csv("assets/gen/soils_condition.csv").unwrap(),
+            soils: load_ranges("assets/gen/soils_condition.csv"),
         }
     }
+}
 
-    pub fn gen(&self, world: &VoxelWorld, col: ColPos) {
+impl WorldGenerator for DebugGen {
+    fn gen(&self, world: &VoxelWorld, col: ColPos) {
         for (dx, dz) in iproduct!(0..CHUNK_S1, 0..CHUNK_S1) {
             let (x, z) = (unchunked(col.x, dx), unchunked(col.z, dz));
             let (y, t, h) = values(x, z);
@@ -1,57 +1,152 @@
-use crate::{Block, gen::Soils};
+use crate::{Block, gen::{Plants, Soils}};
 use crate::world::{
-    BlockPos, BlockPos2d, ColPos, VoxelWorld, CHUNK_S1, CHUNK_S1I, MAX_GEN_HEIGHT, WATER_H,
+    BlockPos, BlockPos2d, ChunkPos, Climate, ColClimate, ColPos, ColedPos, TrackedChunk, VoxelWorld, CHUNK_S1, CHUNK_S1I, MAX_GEN_HEIGHT,
 };
-use riverbed_closest::{ranges, ClosestTrait};
+use riverbed_closest::{dither, points, ClosestTrait};
 use bevy::prelude::info_span;
 use itertools::iproduct;
-use noise_algebra::NoiseSource;
-use std::{collections::HashMap, ops::RangeInclusive};
+use noise_algebra::{NoiseSource, Signal2d};
+use std::collections::HashMap;
 
+use super::generator::WorldGenerator;
+use super::biome::{Biome, BiomeGen, Biomes, BIOMES_BLENDED};
+use super::ores::{gen_ores, ores_from_json5, Ores};
+use super::structures::{gen_structures, structures_from_dir, Structures};
+use super::strata::{gen_strata, strata_from_json5, RockColumn, Strata};
+use super::growables::{tree_rank, tree_shapes_from_json5, TreeShapes};
 use super::tree::Trees;
-pub const CONT_R: f32 = (WATER_H + 2) as f32 / MAX_GEN_HEIGHT as f32;
-pub const CONT_COMPL: f32 = 1. - CONT_R;
+use super::preset::WorldPreset;
+use super::{load_ranges, pos_to_range, pos_to_range3d};
+/// Caves carved this far below sea level are flooded where the aquifer noise is high enough
+const AQUIFER_DEPTH: i32 = 16;
+/// Land ratio of the terrain as the noise makes it, other ratios raise or sink the terrain
+const BASE_LAND_RATIO: f32 = 0.4;
+const BEDROCK_H: i32 = 3;
+const TUNNEL_W: f32 = 0.03;
+const PLANT_SPACING: usize = 3;
+const PLANT_SALT: i32 = 0x5eed;
+/// Soils matching the climate with a lower score are dithered with the next best soil
+const SOIL_DITHER: f32 = 0.15;
+const DITHER_SALT: i32 = 0xd17;
+/// Blocks sampled around a column so that slopes are central differences up to its borders
+const MARGIN: usize = 1;
+/// Maximum distance from the heightmap surface that the 3D density pass can carve or add blocks
+const DENSITY_BAND: f32 = 24.;
+/// Columns with a lower density weight keep the plain heightmap
+const DENSITY_MIN_WEIGHT: f32 = 0.05;
+const DENSITY_SALT: i32 = 0xd3d;
+/// Maximum vertical shift of the strata boundaries
+const STRATA_WARP: f32 = 8.;
+const STRATA_SALT: i32 = 0x57a7a;
+/// Deepest water over the continental shelves
+const SHELF_DEPTH: i32 = 12;
+/// Continentalness under which the shelves slope down to the abyss
+const SHELF_CONT: f32 = 0.4;
+/// Extra depth of the open ocean past the shelves
+const ABYSS_DEPTH: f32 = 48.;
+const TRENCH_DEPTH: f32 = 40.;
+/// Mountain control above which mountains meeting the sea make cliffs
+const CLIFF_MOUNTAINS: f32 = 0.6;
+const CLIFF_H: f32 = 14.;
+/// Height of the widest beaches above sea level, beaches narrow as the slope increases
+const BEACH_H: i32 = 3;
+/// Slope at which beaches vanish
+const BEACH_SLOPE: i32 = 3;
+/// Sea floors at least this steep are covered in gravel
+const GRAVEL_SLOPE: i32 = 3;
+/// Humidity above which calm shallow waters have a muddy floor
+const MUD_HUM: f32 = 0.6;
+/// Temperature drop from sea level to the top of the generated terrain
+const LAPSE: f32 = 0.5;
 
 pub struct Earth {
     soils: Soils,
     trees: Trees,
+    tree_shapes: TreeShapes,
+    ores: Ores,
+    strata: Strata,
+    structures: Structures,
+    biomes: Biomes,
+    land_plants: Plants,
+    water_plants: Plants,
     seed: i32,
-    config: HashMap<String, f32>,
+    sea_level: i32,
+    /// Added to the terrain height to match the preset's land ratio
+    land_offset: i32,
+    mountain_scale: f32,
+    continent_scale: f32,
+    rift_frequency: f32,
+    overhangs: f32,
 }
 
-fn pos_to_range(pos: ColPos) -> [RangeInclusive<i32>; 2] {
-    let x = pos.z * CHUNK_S1I;
-    let y = pos.x * CHUNK_S1I;
-    [x..=(x + CHUNK_S1I - 1), y..=(y + CHUNK_S1I - 1)]
+/// Blocks written down from `top` by the heightmap pass, as `VoxelWorld::set_yrange` does
+struct Fill {
+    pos: ColedPos,
+    top: i32,
+    height: usize,
+    block: Block,
+}
+
+/// 2D signals of a column, all in [0; 1]
+struct ColNoise {
+    ys: Signal2d,
+    ts: Signal2d,
+    hs: Signal2d,
+    ph: Signal2d,
+    rocks: Signal2d,
+    mountains: Signal2d,
+    continentalness: Signal2d,
+    trench: Signal2d,
+    rift: Signal2d,
+    trees: Signal2d,
 }
 
 impl Earth {
-    pub fn new(seed: u32, config: HashMap<String, f32>) -> Self {
+    pub fn new(preset: &WorldPreset) -> Self {
+        let (water_plants, land_plants): (Plants, Plants) = 
+            load_ranges::<4, Block>("assets/gen/plants_condition.csv")
+            .into_iter()
+            .partition(|(_, plant)| plant.grows_on_water());
         Earth {
-            soils: ranges::from_csv("assets/gen/soils_condition.csv").unwrap(),
-            trees: ranges::from_csv("assets/gen/trees_condition.csv").unwrap(),
-            seed: seed as i32,
-            config,
+            soils: load_ranges("assets/gen/soils_condition.csv"),
+            trees: load_ranges("assets/gen/trees_condition.csv"),
+            tree_shapes: tree_shapes_from_json5("assets/gen/trees.json5").unwrap(),
+            ores: ores_from_json5("assets/gen/ores.json5").unwrap(),
+            strata: strata_from_json5("assets/gen/strata.json5").unwrap(),
+            structures: structures_from_dir("assets/gen/structures").unwrap(),
+            biomes: points::from_csv("assets/gen/biomes.csv").unwrap_or_else(|err| panic!("{err}")).into(),
+            land_plants,
+            water_plants,
+            seed: preset.seed() as i32,
+            sea_level: preset.sea_level,
+            land_offset: ((preset.land_ratio - BASE_LAND_RATIO) * preset.sea_level as f32) as i32,
+            mountain_scale: preset.mountain_scale,
+            continent_scale: preset.continent_scale,
+            rift_frequency: preset.rift_frequency,
+            overhangs: preset.overhangs,
         }
     }
 
-    pub fn gen(&self, world: &VoxelWorld, col: ColPos) {
-        //let landratio = self.config.get("land_ratio").copied().unwrap_or(0.4);
-        let range = pos_to_range(col);
+    /// Signals of the column and of `margin` blocks around it, signals are indexed from the corner of the margin
+    fn noise(&self, col: ColPos, margin: i32) -> ColNoise {
+        // continents reach just above sea level, mountains make up the rest
+        let cont_r = (self.sea_level + 2) as f32 / MAX_GEN_HEIGHT as f32;
+        let range = pos_to_range(col).map(|range| (range.start() - margin)..=(range.end() + margin));
         let gen_span = info_span!("noise gen", name = "noise gen").entered();
         let mut n = NoiseSource::new(range, self.seed, 1);
-        let continentalness = n.simplex(0.2);
+        let continentalness = n.simplex(0.2 * self.continent_scale);
+        let cont_control = continentalness.clone().normalize();
         let cont =
             (n.simplex(1.) * 0.3 + n.simplex(5.) * 0.1 + n.simplex(20.) * 0.05 + &continentalness)
                 .normalize()
-                .cap(CONT_R);
+                .cap(cont_r);
         let rocks =
             !(n.simplex(0.5) + n.simplex(4.) * 0.2 + n.simplex(16.) * 0.1 + n.simplex(80.) * 0.05)
                 .normalize()
                 .cap(0.08);
         let mountain_control = n.ridge(0.2);
         let mountain = (n.simplex(2.) + n.simplex(10.) * 0.1 + n.simplex(50.) * 0.005).normalize()
-            * mountain_control.powi(2);
+            * mountain_control.clone().powi(2);
         let ts = (n.simplex(0.05)
             + n.simplex(0.4) * 0.1
             + n.simplex(8.) * 0.05
@@ -63,68 +158,237 @@ impl Earth {
             + n.simplex(60.) * 0.04)
             .normalize();
         let ph = (n.simplex(1.) + n.simplex(4.) * 0.2 + n.simplex(40.) * 0.1).normalize();
-        let rift_control = (n.ridge(1.) + n.ridge(20.) * 0.05).normalize().powi(2);
-        let rift = ((n.simplex(0.3) + n.simplex(1.) * 0.2 + !ph.clone() * 0.5).normalize()
+        let rift_control = (n.ridge(self.rift_frequency) + n.ridge(20.) * 0.05).normalize().powi(2);
+        let rift = ((n.simplex(0.3 * self.rift_frequency) + n.simplex(1.) * 0.2 + !ph.clone() * 0.5).normalize()
             * rift_control)
             .threshold(0.9);
         let trees =
             (n.simplex(1.) + &hs * 0.3 + n.simplex(5.) * 0.4 + n.simplex(20.) * 0.2).normalize();
-        let iron = (n.simplex(8.) + n.simplex(16.) * 0.1).normalize();
-        let ys = cont + &mountain * CONT_COMPL + &rocks;
-        // convert y to convenient values
-        let ys = ys.map(|y| (y * MAX_GEN_HEIGHT as f32) as i32);
-        let rift = rift.map(|r| (r * (MAX_GEN_HEIGHT / 2) as f32) as i32);
+        let trench = (n.ridge(0.4) + n.ridge(8.) * 0.05).normalize().threshold(0.85);
+        let ys = cont + &mountain * ((1. - cont_r) * self.mountain_scale) + &rocks;
         gen_span.exit();
+        ColNoise { ys, ts, hs, ph, rocks, mountains: mountain_control, continentalness: cont_control, trench, rift, trees }
+    }
+
+    /// How much the 3D density pass reshapes a column, in [0; 1] so that the band never reaches past the
+    /// 30 blocks of soil, cobblestone and granite under the heightmap surface
+    fn density_weight(&self, mountains: f32, rocks: f32) -> f32 {
+        ((((mountains - 0.5) * 2.).max(0.) + rocks / 0.08).min(1.) * self.overhangs).min(1.)
+    }
+
+    /// Temperature at height `y` of a column with temperature `temp` at sea level
+    fn lapse(&self, temp: f32, y: i32) -> f32 {
+        let elevation = (y - self.sea_level).max(0) as f32 / (MAX_GEN_HEIGHT as i32 - self.sea_level) as f32;
+        (temp - elevation * LAPSE).max(0.)
+    }
+
+    /// Height of the terrain once shaped by the coast: shelves sloping down to the abyss where the
+    /// continentalness is low, trenches in the open ocean and cliffs where mountains meet the sea
+    fn coast_height(&self, base_y: i32, continentalness: f32, mountains: f32, trench: f32) -> i32 {
+        let cliff = ((mountains - CLIFF_MOUNTAINS) / (1. - CLIFF_MOUNTAINS)).clamp(0., 1.);
+        if base_y > self.sea_level {
+            return base_y.max(self.sea_level + (cliff * CLIFF_H) as i32);
+        }
+        let depth = self.sea_level - base_y;
+        let open_sea = ((SHELF_CONT - continentalness) / SHELF_CONT).clamp(0., 1.);
+        // cliffs drop straight into deep water without a shelf
+        let shelf = if cliff > 0. { depth } else { depth.min(SHELF_DEPTH) };
+        let depth = shelf + ((open_sea.sqrt() + cliff) * ABYSS_DEPTH + trench * open_sea * TRENCH_DEPTH) as i32;
+        (self.sea_level - depth).max(BEDROCK_H + 1)
+    }
+
+    /// Floor of the sea `depth` blocks under the surface
+    fn sea_floor(&self, depth: i32, hum: f32, slope: i32) -> Block {
+        if slope >= GRAVEL_SLOPE || depth > SHELF_DEPTH {
+            Block::Gravel
+        } else if hum > MUD_HUM && depth > 1 {
+            Block::Mud
+        } else {
+            Block::Sand
+        }
+    }
+
+    /// Writes the heightmap fills of a column, carving and adding stone around the surface of mountains and rocky areas
+    /// to make overhangs, arches and cliffs. The density is known before the fills are written so carved blocks are never
+    /// written at all, leaves that trees of neighbouring columns put there stay whichever column is generated first.
+    /// Only touches blocks above sea level and updates `surface` to the new top of the terrain.
+    fn fill_with_density(&self, world: &VoxelWorld, col: ColPos, fills: &[Fill], weights: &[[f32; CHUNK_S1]; CHUNK_S1], surface: &mut [[i32; CHUNK_S1]; CHUNK_S1]) {
+        let heights = *surface;
+        let mut band_lows = [[i32::MAX; CHUNK_S1]; CHUNK_S1];
+        let mut bands = Vec::new();
+        for (dx, dz) in iproduct!(0..CHUNK_S1, 0..CHUNK_S1) {
+            if weights[dx][dz] < DENSITY_MIN_WEIGHT {
+                continue;
+            }
+            let band = (DENSITY_BAND * weights[dx][dz]) as i32;
+            let band_low = (heights[dx][dz] - band).max(self.sea_level + 1);
+            band_lows[dx][dz] = band_low;
+            bands.push((band_low, heights[dx][dz] + band));
+            // starts at the untouched terrain below the band and is raised by every solid block of the band
+            surface[dx][dz] = heights[dx][dz].min(band_low - 1);
+        }
+        // blocks of the fills inside a band, indexed from the bottom of the band, written once the density is known
+        let mut shells: HashMap<ColedPos, Vec<Option<Block>>> = HashMap::new();
+        for fill in fills {
+            let (dx, dz) = fill.pos;
+            let band_low = band_lows[dx][dz];
+            let mut height = fill.height as i32;
+            if fill.top >= band_low {
+                let shell = shells.entry(fill.pos).or_insert_with(|| vec![None; (heights[dx][dz] - band_low + 1) as usize]);
+                for y in (fill.top - height).max(band_low)..=fill.top {
+                    shell[(y - band_low) as usize] = Some(fill.block);
+                }
+                height -= fill.top - band_low + 1;
+            }
+            if height > 0 {
+                world.set_yrange(col, fill.pos, fill.top.min(band_low - 1), height as usize, fill.block);
+            }
+        }
+        // flat areas keep the cheap heightmap path
+        let (Some(low), Some(high)) = (bands.iter().map(|band| band.0).min(), bands.iter().map(|band| band.1).max()) else {
+            return;
+        };
+        let [z_range, _, x_range] = pos_to_range3d(col, 0);
+        let mut n = NoiseSource::new([z_range, low..=high, x_range], self.seed ^ DENSITY_SALT, 1);
+        let density = (n.simplex(2.) + n.simplex(6.) * 0.4 + n.simplex(20.) * 0.1).normalize();
+        for cy in (low / CHUNK_S1I)..=(high / CHUNK_S1I) {
+            let chunk_pos = ChunkPos {
+                x: col.x,
+                y: cy,
+                z: col.z,
+                realm: col.realm,
+            };
+            let mut chunk = world.chunks.entry(chunk_pos).or_insert_with(|| TrackedChunk::new());
+            for (dx, dy, dz) in iproduct!(0..CHUNK_S1, 0..CHUNK_S1, 0..CHUNK_S1) {
+                let weight = weights[dx][dz];
+                let (y, top) = (cy * CHUNK_S1I + dy as i32, heights[dx][dz]);
+                let band = DENSITY_BAND * weight;
+                if weight < DENSITY_MIN_WEIGHT || y < low || y > high || y <= self.sea_level || (y - top).abs() as f32 > band {
+                    continue;
+                }
+                // positive inside the terrain, the noise pushes the surface up or down by up to a band
+                let d = (top - y) as f32 / band + (density[[dx, (y - low) as usize, dz]] - 0.5) * 2.;
+                let solid = if y <= top {
+                    match shells.get(&(dx, dz)).and_then(|shell| shell[(y - band_lows[dx][dz]) as usize]) {
+                        Some(block) if d >= 0. => {
+                            chunk.set((dx, dy, dz), block);
+                            block != Block::Air
+                        }
+                        // carved, whatever is there is left as is
+                        _ => false,
+                    }
+                } else if d > 0. && tree_rank(*chunk.get((dx, dy, dz))).is_some() {
+                    // leaves from trees of neighbouring columns are replaced like the rest of the terrain does
+                    chunk.set((dx, dy, dz), Block::Cobblestone);
+                    true
+                } else {
+                    false
+                };
+                if solid {
+                    surface[dx][dz] = y;
+                }
+            }
+        }
+    }
+}
+
+impl WorldGenerator for Earth {
+    fn gen(&self, world: &VoxelWorld, col: ColPos) {
+        let ColNoise { ys, ts, hs, ph, rocks, mountains, continentalness, trench, rift, trees } = self.noise(col, MARGIN as i32);
+        // convert y to convenient values
+        let ys = ys.map(|y| (y * MAX_GEN_HEIGHT as f32) as i32 + self.land_offset);
+        // indexed like the signals, from the corner of the margin
+        let heights: [[i32; CHUNK_S1 + 2 * MARGIN]; CHUNK_S1 + 2 * MARGIN] = core::array::from_fn(|x| core::array::from_fn(|z| {
+            self.coast_height(ys[[x, z]], continentalness[[x, z]], mountains[[x, z]], trench[[x, z]])
+        }));
+        // steepest height difference with the neighbouring blocks, the margin makes it continuous across columns
+        let slope_at = |[x, z]: [usize; 2]| {
+            (heights[x + 1][z] - heights[x - 1][z]).abs().max((heights[x][z + 1] - heights[x][z - 1]).abs())
+        };
+        let rift_depth = if self.rift_frequency > 0. { (MAX_GEN_HEIGHT / 2) as f32 } else { 0. };
+        let rift = rift.map(|r| (r * rift_depth) as i32);
+        let mut biome_gens: HashMap<Biome, Option<BiomeGen>> = HashMap::new();
+        // top of the terrain after biome rules are applied
+        let mut surface = [[0; CHUNK_S1]; CHUNK_S1];
+        let mut density_weights = [[0.; CHUNK_S1]; CHUNK_S1];
+        let mut fills = Vec::new();
+        let mut climate = ColClimate::new();
         let fill_span = info_span!("chunk filling", name = "chunk filling").entered();
         for (dx, dz) in iproduct!(0..CHUNK_S1, 0..CHUNK_S1) {
-            let (base_y, t, h, rocks, rift, iron) = (
-                ys[[dx, dz]],
-                ts[[dx, dz]],
-                hs[[dx, dz]],
-                rocks[[dx, dz]],
-                rift[[dx, dz]],
-                iron[[dx, dz]],
+            let i = [dx + MARGIN, dz + MARGIN];
+            let (base_y, t, h, rocks, rift) = (
+                heights[i[0]][i[1]],
+                ts[i],
+                hs[i],
+                rocks[i],
+                rift[i],
             );
-            let y = (base_y - rift).max(1);
+            let biomes = self.biomes.blend([t, h], BIOMES_BLENDED);
+            for (biome, _) in biomes.iter() {
+                biome_gens.entry(**biome).or_insert_with(|| biome.col_gen(pos_to_range(col), self.seed));
+            }
+            let rng = <BlockPos2d>::from((col, (dx, dz))).prng(self.seed ^ DITHER_SALT);
+            let dither_r = |shift: usize| ((rng >> shift) & 0xff) as f32 / 256.;
+            let is_land = base_y > self.sea_level && rift == 0;
+            let mut y = (base_y - rift).max(1);
+            if is_land {
+                // height modifiers are interpolated, layers come from a single biome picked at random
+                let height_mod: f32 = biomes.iter()
+                    .map(|(biome, weight)| biome_gens[*biome].as_ref().map_or(0, |gen| gen.height_mod.at((dx, dz))) as f32 * weight)
+                    .sum();
+                y = (y + height_mod.round() as i32).max(self.sea_level + 1);
+            }
+            let biome_gen = &biome_gens[dither(&biomes, dither_r(0))];
+            // biomes follow the climate at sea level, soils and tints the one at the surface
+            let t = self.lapse(t, y);
+            climate.set((dx, dz), Climate::new(t, h, ph[i], *biomes[0].0));
+            let slope = slope_at(i);
+            let beach_h = BEACH_H * (BEACH_SLOPE - slope).max(0) / BEACH_SLOPE;
             let block = if rocks > 0.001 || rift > 6 {
                 Block::Cobblestone
-            } else if base_y <= WATER_H {
+            } else if base_y <= self.sea_level {
+                self.sea_floor(self.sea_level - base_y, h, slope)
+            } else if base_y <= self.sea_level + beach_h {
                 Block::Sand
+            } else if slope > CLIFF_H as i32 / 2 && base_y <= self.sea_level + CLIFF_H as i32 {
+                // bare rock on the sea cliffs
+                Block::Cobblestone
             } else {
                 let (block, value) = self.soils.closest([t, h]);
                 if value < 0. {
                     Block::Dirt
+                } else if value < SOIL_DITHER {
+                    *dither(&self.soils.blend([t, h], 2), dither_r(8))
                 } else {
                     *block
                 }
             };
-            world.set_yrange(col, (dx, dz), y, 4, block);
-            world.set_yrange(col, (dx, dz), y - 4, 2, Block::Cobblestone);
-            world.set_yrange(col, (dx, dz), y - 6, 24, Block::Granite);
-            if rift > 18 && rift < 24 && iron > 0.9 {
-                let height = if iron > 0.97 {
-                    4
-                } else if iron > 0.94 {
-                    3
-                } else if iron > 0.91 {
-                    2
-                } else {
-                    1
-                };
-                world.set_yrange(col, (dx, dz), y, height, Block::IronOre)
+            fills.push(Fill { pos: (dx, dz), top: y, height: 4, block });
+            fills.push(Fill { pos: (dx, dz), top: y - 4, height: 2, block: Block::Cobblestone });
+            fills.push(Fill { pos: (dx, dz), top: y - 6, height: 24, block: Block::Granite });
+            if let (Some(biome_gen), true) = (biome_gen.as_ref(), is_land) {
+                for layer in biome_gen.layers.iter() {
+                    let height = layer.height.at((dx, dz));
+                    if height > 0 {
+                        y += height;
+                        fills.push(Fill { pos: (dx, dz), top: y, height: height as usize, block: layer.block });
+                    }
+                }
+            }
+            surface[dx][dz] = y;
+            if is_land {
+                density_weights[dx][dz] = self.density_weight(mountains[i], rocks);
             }
-            let water_height = WATER_H - base_y;
+            let water_height = self.sea_level - base_y;
             if water_height > 0 {
-                world.set_yrange(
-                    col,
-                    (dx, dz),
-                    WATER_H,
-                    (water_height + rift) as usize,
-                    Block::SeaBlock,
-                );
+                fills.push(Fill { pos: (dx, dz), top: self.sea_level, height: (water_height + rift) as usize, block: Block::SeaBlock });
             }
         }
+        world.set_climate(col, climate);
         fill_span.exit();
+        let density_span = info_span!("density pass", name = "density pass").entered();
+        self.fill_with_density(world, col, &fills, &density_weights, &mut surface);
+        density_span.exit();
         let tree_span = info_span!("tree gen", name = "tree gen").entered();
         let tree_spots = [
             (0, 0),
@@ -146,20 +410,21 @@ impl Earth {
             let rng = <BlockPos2d>::from((col, spot)).prng(self.seed);
             let dx = spot.0 + (rng & 0b111);
             let dz = spot.1 + ((rng >> 3) & 0b111);
-            if rift[[dx, dz]] > 0 {
+            let i = [dx + MARGIN, dz + MARGIN];
+            if rift[i] > 0 {
                 continue;
             }
-            let tree = trees[[dx, dz]];
+            let tree = trees[i];
             if tree < 0.5 {
                 continue;
             }
             let h = (rng >> 5) & 0b11;
-            let y = ys[[dx, dz]];
-            if y > WATER_H {
+            let y = surface[dx][dz];
+            if y > self.sea_level {
                 let (tree, dist) = self.trees.closest([
-                    ts[[dx, dz]],
-                    hs[[dx, dz]],
-                    ph[[dx, dz]],
+                    self.lapse(ts[i], y),
+                    hs[i],
+                    ph[i],
                     y as f32 / MAX_GEN_HEIGHT as f32,
                 ]);
                 if dist >= 0. {
@@ -169,10 +434,257 @@ impl Earth {
                         z: col.z * CHUNK_S1I + dz as i32,
                         realm: col.realm,
                     };
-                    tree.grow(world, pos, self.seed, dist + h as f32 / 10.);
+                    tree.grow(&self.tree_shapes, world, pos, self.seed, dist + h as f32 / 10.);
                 }
             }
         }
         tree_span.exit();
+        let plant_span = info_span!("plant gen", name = "plant gen").entered();
+        for (gx, gz) in iproduct!((0..CHUNK_S1).step_by(PLANT_SPACING), (0..CHUNK_S1).step_by(PLANT_SPACING)) {
+            let rng = <BlockPos2d>::from((col, (gx, gz))).prng(self.seed ^ PLANT_SALT);
+            let dx = (gx + rng % PLANT_SPACING).min(CHUNK_S1 - 1);
+            let dz = (gz + (rng >> 4) % PLANT_SPACING).min(CHUNK_S1 - 1);
+            let i = [dx + MARGIN, dz + MARGIN];
+            if rift[i] > 0 {
+                continue;
+            }
+            let y = surface[dx][dz];
+            let (plants, ground_y) = if y > self.sea_level { 
+                (&self.land_plants, y)
+            } else {
+                (&self.water_plants, self.sea_level)
+            };
+            if plants.is_empty() {
+                continue;
+            }
+            let (plant, score) = plants.closest([
+                self.lapse(ts[i], ground_y),
+                hs[i],
+                ph[i],
+                ground_y as f32 / MAX_GEN_HEIGHT as f32,
+            ]);
+            // better matching plants are denser
+            if score < 0. || ((rng >> 8) & 0xff) as f32 / 256. > 0.2 + score * 0.6 {
+                continue;
+            }
+            let ground = BlockPos::from((col, (dx, ground_y, dz)));
+            let ground_block = world.get_block(ground);
+            if (y > self.sea_level && !ground_block.is_fertile_soil()) || (y <= self.sea_level && ground_block != Block::SeaBlock) {
+                continue;
+            }
+            world.set_if_empty(ground + (0, 1, 0), *plant);
+        }
+        plant_span.exit();
+        let structure_span = info_span!("structure gen", name = "structure gen").entered();
+        gen_structures(&self.structures, world, col, self.seed, self.sea_level, &surface);
+        structure_span.exit();
+    }
+
+    /// Fills everything below the surface layers with stone down to a bedrock floor, carving caves and aquifers,
+    /// then lays the strata and ores in it.
+    /// Runs as a separate pass so that surfaces can be generated first.
+    fn gen_underground(&self, world: &VoxelWorld, col: ColPos) {
+        let floors: [[i32; CHUNK_S1]; CHUNK_S1] = core::array::from_fn(|dx| core::array::from_fn(
+            |dz| world.bottom_block(col, (dx, dz)).unwrap_or(0)
+        ));
+        let top = floors.iter().flatten().copied().max().unwrap_or(0);
+        if top <= 0 {
+            return;
+        }
+        let gen_span = info_span!("cave noise gen", name = "cave noise gen").entered();
+        let mut n = NoiseSource::new(pos_to_range3d(col, top), self.seed, 1);
+        let tunnel_a = (n.simplex(3.) + n.simplex(12.) * 0.1).normalize();
+        let tunnel_b = (n.simplex(3.) + n.simplex(12.) * 0.1).normalize();
+        let cavern = (n.simplex(1.) + n.simplex(6.) * 0.3 + n.simplex(24.) * 0.05).normalize();
+        let aquifer = (n.simplex(0.5) + n.simplex(4.) * 0.1).normalize();
+        gen_span.exit();
+        let fill_span = info_span!("underground filling", name = "underground filling").entered();
+        for cy in 0..=(top / CHUNK_S1I) {
+            let chunk_pos = ChunkPos {
+                x: col.x,
+                y: cy,
+                z: col.z,
+                realm: col.realm,
+            };
+            let mut chunk = world.chunks.entry(chunk_pos).or_insert_with(|| TrackedChunk::new());
+            for (dx, dy, dz) in iproduct!(0..CHUNK_S1, 0..CHUNK_S1, 0..CHUNK_S1) {
+                let y = cy * CHUNK_S1I + dy as i32;
+                if y >= floors[dx][dz] {
+                    continue;
+                }
+                let block = if y < BEDROCK_H {
+                    let rng = BlockPos::from((col, (dx, y, dz))).prng(self.seed);
+                    if y == 0 || rng % BEDROCK_H as usize >= y as usize {
+                        Block::Bedrock
+                    } else {
+                        Block::Granite
+                    }
+                } else {
+                    let i = [dx, y as usize, dz];
+                    let is_tunnel = (tunnel_a[i] - 0.5).abs() < TUNNEL_W && (tunnel_b[i] - 0.5).abs() < TUNNEL_W;
+                    let is_cavern = y > BEDROCK_H + 2 && cavern[i] > 0.8;
+                    if !is_tunnel && !is_cavern {
+                        Block::Granite
+                    } else if y <= self.sea_level - AQUIFER_DEPTH && aquifer[i] > 0.6 {
+                        Block::SeaBlock
+                    } else {
+                        continue;
+                    }
+                };
+                chunk.set((dx, dy, dz), block);
+            }
+        }
+        fill_span.exit();
+        let strata_span = info_span!("strata gen", name = "strata gen").entered();
+        let ColNoise { ys, ts, hs, .. } = self.noise(col, 0);
+        let mut n = NoiseSource::new(pos_to_range(col), self.seed ^ STRATA_SALT, 1);
+        let region = (n.simplex(0.3) + n.simplex(2.) * 0.2).normalize();
+        let warp = (n.simplex(1.5) + n.simplex(6.) * 0.3).normalize();
+        gen_strata(&self.strata, world, col, Block::Granite, |dx, dz| RockColumn {
+            surface: (ys[[dx, dz]] * MAX_GEN_HEIGHT as f32) as i32 + self.land_offset - self.sea_level,
+            temp: ts[[dx, dz]],
+            hum: hs[[dx, dz]],
+            region: region[[dx, dz]],
+            warp: ((warp[[dx, dz]] - 0.5) * 2. * STRATA_WARP) as i32,
+        });
+        strata_span.exit();
+        let ore_span = info_span!("ore gen", name = "ore gen").entered();
+        gen_ores(&self.ores, world, col, self.seed, |dx, dz| (ts[[dx, dz]], hs[[dx, dz]]));
+        ore_span.exit();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::Path, sync::atomic::{AtomicUsize, Ordering}, thread};
+    use itertools::iproduct;
+    use crate::Block;
+    use crate::world::{BlockPos, Chunk, ChunkPos, ColPos, Realm, VoxelWorld, CHUNK_S1, MAX_GEN_HEIGHT, Y_CHUNKS};
+    use crate::gen::{generator::WorldGenerator, growables::{outranks, tree_rank}, preset::WorldPreset};
+    use super::Earth;
+
+    const SNAPSHOT: &str = "src/gen/snapshots/earth.txt";
+    /// Set this variable when running the tests to rewrite the snapshot after an intended change in generation
+    const BLESS_VAR: &str = "RIVERBED_BLESS";
+    const SEED: u64 = 0x5eed_0042;
+
+    fn earth() -> Earth {
+        Earth::new(&WorldPreset { seed: Some(SEED), ..Default::default() })
+    }
+
+    /// A block of neighbouring columns so that trees cross column borders
+    fn grid() -> Vec<ColPos> {
+        iproduct!(-1..=1, -1..=1).map(|(x, z)| ColPos { x, z, realm: Realm::Overworld }).collect()
+    }
+
+    /// Surfaces first and then underground layers like the gen thread, `workers` threads take columns in order
+    fn generate(gen: &Earth, cols: &[ColPos], workers: usize) -> VoxelWorld {
+        let world = VoxelWorld::new();
+        let passes: [fn(&Earth, &VoxelWorld, ColPos); 2] = [Earth::gen, Earth::gen_underground];
+        for pass in passes {
+            let next = AtomicUsize::new(0);
+            thread::scope(|s| for _ in 0..workers {
+                s.spawn(|| while let Some(col) = cols.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    pass(gen, &world, *col);
+                });
+            });
+        }
+        world
+    }
+
+    /// FNV-1a of the encoded chunks, unlike DefaultHasher it is guaranteed to stay the same across Rust versions.
+    /// Missing chunks are hashed as empty ones.
+    fn col_hash(world: &VoxelWorld, col: ColPos) -> u64 {
+        let empty = Chunk::new().encode();
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for y in 0..Y_CHUNKS as i32 {
+            let chunk_pos = ChunkPos { x: col.x, y, z: col.z, realm: col.realm };
+            let bytes = world.chunks.get(&chunk_pos).map_or_else(|| empty.clone(), |chunk| chunk.encode());
+            for byte in bytes.into_iter().chain([0xff]) {
+                hash = (hash ^ byte as u64).wrapping_mul(0x100000001b3);
+            }
+        }
+        hash
+    }
+
+    fn hashes(world: &VoxelWorld, cols: &[ColPos]) -> Vec<String> {
+        cols.iter().map(|col| format!("{} {} {:016x}", col.x, col.z, col_hash(world, *col))).collect()
+    }
+
+    #[test]
+    fn matches_snapshot() {
+        let mut cols = grid();
+        // isolated columns far from the origin
+        cols.extend([(40, -17), (-123, 256), (1000, 999)].map(|(x, z)| ColPos { x, z, realm: Realm::Overworld }));
+        let hashes = hashes(&generate(&earth(), &cols, 1), &cols);
+        if env::var_os(BLESS_VAR).is_some() {
+            fs::create_dir_all(Path::new(SNAPSHOT).parent().unwrap()).unwrap();
+            fs::write(SNAPSHOT, hashes.join("\n") + "\n").unwrap();
+            return;
+        }
+        let expected = fs::read_to_string(SNAPSHOT)
+            .unwrap_or_else(|_| panic!("no snapshot at {SNAPSHOT}, run the tests with {BLESS_VAR}=1 to create it"));
+        let changed: Vec<_> = hashes.iter().zip(expected.lines())
+            .filter(|(hash, expected)| hash != expected)
+            .map(|(hash, _)| hash.rsplit_once(' ').unwrap().0)
+            .collect();
+        assert!(
+            changed.is_empty() && hashes.len() == expected.lines().count(),
+            "generation changed for columns {changed:?}, if this is intended run the tests with {BLESS_VAR}=1 and commit {SNAPSHOT}"
+        );
+    }
+
+    #[test]
+    fn independent_of_order_and_workers() {
+        let gen = earth();
+        let cols = grid();
+        let expected = hashes(&generate(&gen, &cols, 1), &cols);
+        let reversed: Vec<_> = cols.iter().rev().copied().collect();
+        let mut shuffled = cols.clone();
+        shuffled.sort_by_key(|col| col.prng(7));
+        for (order, workers) in [(&reversed, 1), (&shuffled, 1), (&shuffled, 4), (&cols, 9)] {
+            assert_eq!(hashes(&generate(&gen, order, workers), &cols), expected, "{workers} workers, order {order:?}");
+        }
+        // leaves of neighbouring trees in the band carved by the density pass and in the rooms of structures,
+        // placed before and after the column is generated
+        let mut checked = 0;
+        for col in cols.iter().copied() {
+            let leaves = covered_air(&gen, col);
+            if leaves.is_empty() {
+                continue;
+            }
+            let place_leaves = |world: &VoxelWorld| for pos in leaves.iter() {
+                world.set_block_if(*pos, Block::OakLeaves, |block| outranks(Block::OakLeaves, block));
+            };
+            let before = VoxelWorld::new();
+            place_leaves(&before);
+            gen.gen(&before, col);
+            let after = VoxelWorld::new();
+            gen.gen(&after, col);
+            place_leaves(&after);
+            assert_eq!(col_hash(&before, col), col_hash(&after, col), "leaves under the terrain of {col:?}");
+            checked += 1;
+        }
+        assert!(checked > 0, "no column of the grid has air under its terrain");
+    }
+
+    /// Air left under the terrain of a column above sea level, where the density pass carved it or in structures
+    fn covered_air(gen: &Earth, col: ColPos) -> Vec<BlockPos> {
+        let world = VoxelWorld::new();
+        gen.gen(&world, col);
+        let mut covered = Vec::new();
+        for (dx, dz) in iproduct!(0..CHUNK_S1, 0..CHUNK_S1) {
+            let mut under_terrain = false;
+            for y in (gen.sea_level + 1..MAX_GEN_HEIGHT as i32).rev() {
+                let pos = BlockPos::from((col, (dx, y, dz)));
+                let block = world.get_block(pos);
+                if block == Block::Air && under_terrain {
+                    covered.push(pos);
+                } else if tree_rank(block).is_none() {
+                    under_terrain = true;
+                }
+            }
+        }
+        covered
     }
 }
@@ -0,0 +1,79 @@
+use std::fs;
+use itertools::iproduct;
+use crate::Block;
+use crate::world::{ChunkPos, ColClimate, ColPos, TrackedChunk, VoxelWorld, CHUNK_S1};
+use super::{generator::WorldGenerator, preset::WorldPreset};
+
+/// Stacks the layers from bottom to top in every column of `col`
+fn fill_layers(world: &VoxelWorld, col: ColPos, layers: &[(Block, usize)]) {
+    let mut tops = Vec::with_capacity(layers.len());
+    let mut y = -1;
+    for (block, height) in layers {
+        y += *height as i32;
+        tops.push((*block, y, *height));
+    }
+    // set_yrange spills one block below the range, so layers are filled from the top down
+    for (dx, dz) in iproduct!(0..CHUNK_S1, 0..CHUNK_S1) {
+        for (block, top, height) in tops.iter().rev() {
+            if *height > 0 {
+                world.set_yrange(col, (dx, dz), *top, *height, *block);
+            }
+        }
+    }
+    world.set_climate(col, ColClimate::new());
+}
+
+/// Generates nothing, for realms that aren't implemented yet
+pub struct VoidGen;
+
+impl WorldGenerator for VoidGen {
+    fn gen(&self, world: &VoxelWorld, col: ColPos) {
+        // an empty chunk so that the column counts as loaded
+        let chunk_pos = ChunkPos { x: col.x, y: 0, z: col.z, realm: col.realm };
+        world.chunks.entry(chunk_pos).or_insert_with(TrackedChunk::new);
+    }
+}
+
+/// Grass on dirt on stone, the ground is right above sea level
+pub struct FlatGen {
+    layers: Vec<(Block, usize)>,
+}
+
+impl FlatGen {
+    pub fn new(preset: &WorldPreset) -> Self {
+        let height = (preset.sea_level as usize + 1).max(5);
+        FlatGen {
+            layers: vec![
+                (Block::Bedrock, 1),
+                (Block::Granite, height - 4),
+                (Block::Dirt, 3),
+                (Block::GrassBlock, 1),
+            ],
+        }
+    }
+}
+
+impl WorldGenerator for FlatGen {
+    fn gen(&self, world: &VoxelWorld, col: ColPos) {
+        fill_layers(world, col, &self.layers);
+    }
+}
+
+/// Layers read from assets/gen/superflat.json5, bottom to top
+pub struct SuperflatGen {
+    layers: Vec<(Block, usize)>,
+}
+
+impl SuperflatGen {
+    pub fn new() -> Self {
+        SuperflatGen {
+            layers: json5::from_str(&fs::read_to_string("assets/gen/superflat.json5").unwrap()).unwrap(),
+        }
+    }
+}
+
+impl WorldGenerator for SuperflatGen {
+    fn gen(&self, world: &VoxelWorld, col: ColPos) {
+        fill_layers(world, col, &self.layers);
+    }
+}
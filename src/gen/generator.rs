@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+use crate::world::{ColPos, Realm, VoxelWorld};
+use super::{
+    aether_gen::AetherGen, debug_gen::DebugGen, earth_gen::Earth, flat_gen::{FlatGen, SuperflatGen, VoidGen},
+    nether_gen::NetherGen, preset::WorldPreset,
+};
+
+pub trait WorldGenerator: Send + Sync {
+    /// Generates the surface of a column
+    fn gen(&self, world: &VoxelWorld, col: ColPos);
+
+    /// Generates what's below the surface, only called once no surface is left to generate
+    fn gen_underground(&self, _world: &VoxelWorld, _col: ColPos) {}
+}
+
+pub type GeneratorConstructor = fn(&WorldPreset) -> Box<dyn WorldGenerator>;
+
+/// Maps generator names to their constructors
+pub struct GeneratorRegistry(HashMap<String, GeneratorConstructor>);
+
+impl GeneratorRegistry {
+    pub fn new() -> Self {
+        let mut registry = GeneratorRegistry(HashMap::new());
+        registry.register("earth", |preset| Box::new(Earth::new(preset)));
+        registry.register("aether", |preset| Box::new(AetherGen::new(preset)));
+        registry.register("debug", |preset| Box::new(DebugGen::new(preset.seed() as u32)));
+        registry.register("flat", |preset| Box::new(FlatGen::new(preset)));
+        registry.register("nether", |preset| Box::new(NetherGen::new(preset)));
+        registry.register("superflat", |_| Box::new(SuperflatGen::new()));
+        registry.register("void", |_| Box::new(VoidGen));
+        registry
+    }
+
+    pub fn register(&mut self, name: &str, constructor: GeneratorConstructor) {
+        self.0.insert(name.to_string(), constructor);
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.0.contains_key(name)
+    }
+
+    pub fn create(&self, name: &str, preset: &WorldPreset) -> Option<Box<dyn WorldGenerator>> {
+        self.0.get(name).map(|constructor| constructor(preset))
+    }
+}
+
+/// Name of the generator used for each realm of the world
+#[derive(Debug, Clone)]
+pub struct RealmGenerators(pub HashMap<Realm, String>);
+
+impl Default for RealmGenerators {
+    fn default() -> Self {
+        RealmGenerators(HashMap::from([
+            (Realm::Overworld, "earth".to_string()),
+            (Realm::Aether, "aether".to_string()),
+            (Realm::Nether, "nether".to_string()),
+        ]))
+    }
+}
+
+impl RealmGenerators {
+    /// Overrides the generators for this session with `RIVERBED_GEN`, either a generator name for the overworld
+    /// or a list of `realm=generator` separated by commas, ex: `RIVERBED_GEN=flat,nether=earth`
+    pub fn with_env_override(self) -> Self {
+        let mut generators = self;
+        let Ok(value) = std::env::var("RIVERBED_GEN") else {
+            return generators;
+        };
+        for entry in value.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+            let (realm, name) = match entry.split_once('=') {
+                Some((realm, name)) => match realm.trim().parse::<Realm>() {
+                    Ok(realm) => (realm, name.trim()),
+                    Err(_) => {
+                        bevy::log::warn!("Unknown realm '{realm}' in RIVERBED_GEN");
+                        continue;
+                    }
+                },
+                None => (Realm::Overworld, entry),
+            };
+            generators.0.insert(realm, name.to_string());
+        }
+        generators
+    }
+
+    /// Builds the generator of every realm, unknown generator names fall back to the void generator
+    pub fn create(&self, registry: &GeneratorRegistry, preset: &WorldPreset) -> HashMap<Realm, Box<dyn WorldGenerator>> {
+        self.0.iter().map(|(realm, name)| {
+            let gen = registry.create(name, preset).unwrap_or_else(|| {
+                bevy::log::warn!("Unknown generator '{name}' for {realm:?}, using void");
+                Box::new(VoidGen)
+            });
+            (*realm, gen)
+        }).collect()
+    }
+}
@@ -1,15 +1,4 @@
 mod utils;
-mod acacia;
-mod baobab;
-mod oak;
-mod spruce;
-mod sequoia;
-mod birch;
-mod cypress;
-pub use acacia::*;
-pub use baobab::*;
-pub use oak::*;
-pub use spruce::*;
-pub use sequoia::*;
-pub use birch::*;
-pub use cypress::*;
\ No newline at end of file
+mod shape;
+pub use utils::{outranks, tree_rank, Growable};
+pub use shape::*;
@@ -0,0 +1,248 @@
+use std::{collections::HashMap, fs};
+use anyhow::Result;
+use serde::Deserialize;
+use crate::world::{BlockPos, VoxelWorld};
+use crate::Block;
+use super::utils::{leaf_disk, leaf_sphere, place_tree_block, Growable};
+use crate::gen::tree::Tree;
+
+const DIRS: [(i32, i32); 8] = [(-1, 1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)];
+
+/// A length that scales with the size of the tree: `base + scale*size`
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+pub struct Dim {
+    #[serde(default)]
+    pub base: i32,
+    #[serde(default)]
+    pub scale: f32,
+}
+
+impl Dim {
+    pub fn at(&self, size: i32) -> i32 {
+        self.base + (self.scale * size as f32) as i32
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Trunk {
+    /// Height of the tree when it grows in ideal conditions
+    pub height: i32,
+    /// How much the height shrinks as conditions get worse
+    #[serde(default)]
+    pub shrink: f32,
+    /// 1 or 2, wide trunks are 2x2
+    #[serde(default = "default_width")]
+    pub width: i32,
+    /// The trunk shifts by one block in a random direction every `lean` blocks
+    #[serde(default)]
+    pub lean: Option<i32>,
+}
+
+fn default_width() -> i32 {
+    1
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Branches {
+    /// Fraction of the trunk height where branches start
+    pub from: f32,
+    /// Fraction of the trunk height above which branches grow on both sides
+    #[serde(default = "default_both_sides")]
+    pub both_sides: f32,
+    #[serde(default = "default_every")]
+    pub every: i32,
+    /// Number of blocks the branch goes up after leaving the trunk
+    #[serde(default)]
+    pub rise: i32,
+    /// Radius of the leaf disk at the end of the branch, scaled by the height of the branch
+    #[serde(default)]
+    pub leaves: Option<Dim>,
+}
+
+fn default_both_sides() -> f32 {
+    1.
+}
+
+fn default_every() -> i32 {
+    2
+}
+
+/// Leaves of the tree, heights are relative to the top of the trunk
+#[derive(Debug, Deserialize, Clone)]
+pub enum Cluster {
+    /// A single horizontal disk, skipped for trees smaller than `min_height`
+    Disk {
+        y: i32,
+        radius: Dim,
+        #[serde(default)]
+        min_height: i32,
+    },
+    Sphere {
+        y: i32,
+        radius: Dim,
+    },
+    /// Stacked disks as tall as the trunk, widest in the middle, ending in a single leaf
+    Spindle {
+        /// Fraction of the trunk height where the leaves start
+        from: f32,
+    },
+    /// Disks every other block along the trunk, shrinking towards the top
+    Cone {
+        /// Height above the ground of the first disk
+        from: i32,
+    },
+    /// Leaves radiating from the top and drooping at their tip
+    Fronds {
+        length: Dim,
+    },
+    Block {
+        y: i32,
+        #[serde(default)]
+        block: Option<Block>,
+    },
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct TreeShape {
+    pub log: Block,
+    #[serde(default)]
+    pub leaves: Option<Block>,
+    /// Blocks the tree can grow on, fertile soils if not specified
+    #[serde(default)]
+    pub soils: Option<Vec<Block>>,
+    pub trunk: Trunk,
+    #[serde(default)]
+    pub branches: Option<Branches>,
+    #[serde(default)]
+    pub clusters: Vec<Cluster>,
+}
+
+pub type TreeShapes = HashMap<Tree, TreeShape>;
+
+pub fn tree_shapes_from_json5(path: &str) -> Result<TreeShapes> {
+    Ok(json5::from_str(&fs::read_to_string(path)?)?)
+}
+
+impl TreeShape {
+    pub fn can_grow_on(&self, block: Block) -> bool {
+        match &self.soils {
+            Some(soils) => soils.contains(&block),
+            None => block.is_fertile_soil(),
+        }
+    }
+
+    pub fn height(&self, dist: f32) -> i32 {
+        (self.trunk.height - (dist * self.trunk.shrink) as i32).max(1)
+    }
+
+    /// Whether the trunk has room to grow above `pos`
+    pub fn has_room(&self, world: &VoxelWorld, pos: BlockPos, dist: f32) -> bool {
+        itertools::iproduct!(1..=self.height(dist), 0..self.trunk.width, 0..self.trunk.width)
+            .all(|(dy, dx, dz)| {
+                let block = world.get_block_safe(pos + (dx, dy, dz));
+                block == Block::Air || block.is_sapling()
+            })
+    }
+
+    fn trunk_layer(&self, world: &VoxelWorld, pos: BlockPos) {
+        for (dx, dz) in itertools::iproduct!(0..self.trunk.width, 0..self.trunk.width) {
+            place_tree_block(world, pos + (dx, 0, dz), self.log);
+        }
+    }
+
+    fn branch(&self, world: &VoxelWorld, pos: BlockPos, (dir_x, dir_z): (i32, i32), level: i32, branches: &Branches) {
+        let w = self.trunk.width;
+        let mut pos = pos + (if dir_x == 1 { w } else { -1 }, 0, if dir_z == 1 { w } else { -1 });
+        place_tree_block(world, pos, self.log);
+        for _ in 0..branches.rise {
+            pos.y += 1;
+            place_tree_block(world, pos, self.log);
+        }
+        if let (Some(leaves), Some(radius)) = (self.leaves, branches.leaves) {
+            leaf_disk(world, pos + (0, -1, 0), 1, leaves);
+            leaf_disk(world, pos + (dir_x, 0, dir_z), radius.at(level).max(0) as u32, leaves);
+        }
+    }
+
+    fn cluster(&self, world: &VoxelWorld, base: BlockPos, top: BlockPos, height: i32, cluster: &Cluster, rng: usize) {
+        let Some(leaves) = self.leaves else {
+            if let Cluster::Block { y, block: Some(block) } = cluster {
+                place_tree_block(world, top + (0, *y, 0), *block);
+            }
+            return;
+        };
+        match cluster {
+            Cluster::Disk { y, radius, min_height } => if height >= *min_height {
+                leaf_disk(world, top + (0, *y, 0), radius.at(height).max(0) as u32, leaves);
+            },
+            Cluster::Sphere { y, radius } => {
+                leaf_sphere(world, top + (0, *y, 0), radius.at(height).max(0) as u32, leaves);
+            },
+            Cluster::Spindle { from } => {
+                let mut pos = base + (0, height - (height as f32 * (1. - from)) as i32, 0);
+                for i in 0..height {
+                    leaf_disk(world, pos, (1 + i.min(height - i)) as u32 / 2, leaves);
+                    pos.y += 1;
+                }
+                place_tree_block(world, pos, leaves);
+            },
+            Cluster::Cone { from } => {
+                for i in *from..height {
+                    if i % 2 == height % 2 {
+                        leaf_disk(world, base + (0, i, 0), ((height - i + 2) / 2) as u32, leaves);
+                    }
+                }
+            },
+            Cluster::Fronds { length } => {
+                let (dx, dz) = DIRS[(rng >> 3) & 0b111];
+                for (fx, fz) in [(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (-1, -1), (1, -1), (-1, 1)] {
+                    let mut pos = top;
+                    for i in 0..length.at(height) {
+                        pos = pos + (fx, 0, fz);
+                        // fronds on the side the trunk leans towards droop less
+                        if i % 2 == 1 && (fx, fz) != (dx, dz) {
+                            pos.y -= 1;
+                        }
+                        place_tree_block(world, pos, leaves);
+                    }
+                }
+                place_tree_block(world, top, leaves);
+            },
+            Cluster::Block { y, block } => {
+                place_tree_block(world, top + (0, *y, 0), block.unwrap_or(leaves));
+            }
+        }
+    }
+}
+
+impl Growable for TreeShape {
+    fn grow(&self, world: &VoxelWorld, pos: BlockPos, seed: i32, dist: f32) {
+        let height = self.height(dist);
+        let rng = pos.prng(seed);
+        let lean = DIRS[(rng >> 3) & 0b111];
+        // `pos` is the soil, the trunk starts right above it like `has_room` expects
+        let base = pos + (0, 1, 0);
+        let mut top = base;
+        for i in 0..height {
+            if let Some(branches) = &self.branches {
+                if i as f32 >= height as f32 * branches.from && i % branches.every == 0 {
+                    let dir = DIRS[((i as usize / 2) ^ rng) & 0b111];
+                    self.branch(world, top, dir, i, branches);
+                    if i as f32 >= height as f32 * branches.both_sides {
+                        self.branch(world, top, (-dir.0, -dir.1), i, branches);
+                    }
+                }
+            }
+            self.trunk_layer(world, top);
+            top.y += 1;
+            if let Some(every) = self.trunk.lean {
+                if i > 0 && i % every == 0 {
+                    top = top + (lean.0, 0, lean.1);
+                }
+            }
+        }
+        for cluster in &self.clusters {
+            self.cluster(world, base, top, height, cluster, rng);
+        }
+    }
+}
@@ -1,8 +1,40 @@
 use crate::world::{BlockPos, VoxelWorld};
-use crate::Block;
+use crate::{Block, BlockFamily};
 
 pub trait Growable: Send + Sync {
-    fn grow(&self, dist: f32, pos: BlockPos, world: &VoxelWorld);
+    fn grow(&self, world: &VoxelWorld, pos: BlockPos, seed: i32, dist: f32);
+}
+
+/// Rank of the blocks that trees can replace, None for the ones they can't
+pub fn tree_rank(block: Block) -> Option<u8> {
+    let families = block.families();
+    if block == Block::Air {
+        Some(0)
+    } else if block.is_plant() {
+        Some(1)
+    } else if families.contains(&BlockFamily::Leaves) {
+        Some(2)
+    } else if families.contains(&BlockFamily::Log) || matches!(block, Block::Cactus | Block::Bamboo) {
+        Some(3)
+    } else {
+        None
+    }
+}
+
+/// Whether a tree block replaces `current` where trees overlap, by rank and then by name
+/// so that the result doesn't depend on which tree grew first
+pub fn outranks(block: Block, current: Block) -> bool {
+    let rank = tree_rank(block).unwrap_or(3);
+    match tree_rank(current) {
+        Some(current_rank) if current_rank == rank => current != block && block.to_string() > current.to_string(),
+        Some(current_rank) => current_rank < rank,
+        None => false,
+    }
+}
+
+/// Places a block of a tree, overlapping trees are resolved with `outranks`
+pub fn place_tree_block(world: &VoxelWorld, pos: BlockPos, block: Block) {
+    world.set_block_if(pos, block, |current| outranks(block, current));
 }
 
 #[inline]
@@ -22,7 +54,7 @@ pub fn leaf_disk(world: &VoxelWorld, center: BlockPos, dist: u32, leaf: Block) {
         let max_x = ((dist.pow(2)-z.pow(2)) as f32).sqrt() as i32;
         for x in 0..=max_x {
             for (dx, dz) in signed_comb(x, z) {
-                world.set_if_empty(BlockPos {
+                place_tree_block(world, BlockPos {
                     realm: center.realm, 
                     x: center.x + dx,
                     y: center.y,
@@ -32,3 +64,12 @@ pub fn leaf_disk(world: &VoxelWorld, center: BlockPos, dist: u32, leaf: Block) {
         }
     }
 }
+
+#[inline]
+pub fn leaf_sphere(world: &VoxelWorld, center: BlockPos, dist: u32, leaf: Block) {
+    let dist = dist as i32;
+    for y in -dist..=dist {
+        let radius = ((dist.pow(2)-y.pow(2)) as f32).sqrt() as u32;
+        leaf_disk(world, center + (0, y, 0), radius, leaf);
+    }
+}
@@ -1,13 +1,51 @@
 mod terrain_gen;
 mod debug_gen;
 mod earth_gen;
+mod aether_gen;
+mod nether_gen;
 mod tree;
 mod biome;
 mod growables;
+mod ores;
+mod structures;
+mod strata;
+mod generator;
+mod flat_gen;
+mod preset;
 
 pub use terrain_gen::setup_gen_thread;
+pub use generator::{GeneratorRegistry, WorldGenerator};
+pub use preset::WorldPreset;
+pub use biome::Biome;
+pub use tree::{Tree, Trees};
+pub use growables::{tree_shapes_from_json5, TreeShapes};
 
-use std::ops::Range;
+use std::{ops::{Range, RangeInclusive}, str::FromStr};
+use bevy::log::warn;
+use riverbed_closest::ranges::{self, Ranges};
 use crate::Block;
+use crate::world::{ColPos, CHUNK_S1I};
 
 type Soils = Vec<([Range<f32>; 2], Block)>;
+type Plants = Vec<([Range<f32>; 4], Block)>;
+
+/// Loads a condition table the game can't run without, logging the ranges that are probably typos
+pub(crate) fn load_ranges<const D: usize, E: FromStr>(path: &str) -> Ranges<D, E> {
+    let (ranges, warnings) = ranges::from_file_checked(path).unwrap_or_else(|err| panic!("{err}"));
+    for warning in warnings {
+        warn!("{path}: {warning}");
+    }
+    ranges
+}
+
+/// Ranges of block coordinates covered by the column, as given to the noise sources
+fn pos_to_range(pos: ColPos) -> [RangeInclusive<i32>; 2] {
+    let x = pos.z * CHUNK_S1I;
+    let y = pos.x * CHUNK_S1I;
+    [x..=(x + CHUNK_S1I - 1), y..=(y + CHUNK_S1I - 1)]
+}
+
+fn pos_to_range3d(pos: ColPos, top: i32) -> [RangeInclusive<i32>; 3] {
+    let [z_range, x_range] = pos_to_range(pos);
+    [z_range, 0..=top, x_range]
+}
@@ -0,0 +1,121 @@
+use crate::Block;
+use crate::world::{
+    BlockPos, ChunkPos, Climate, ColClimate, ColPos, TrackedChunk, VoxelWorld, CHUNK_S1, CHUNK_S1I, MAX_HEIGHT, Y_CHUNKS,
+};
+use bevy::prelude::info_span;
+use itertools::iproduct;
+use noise_algebra::NoiseSource;
+
+use super::generator::WorldGenerator;
+use super::biome::Biome;
+use super::ores::{gen_ores, ores_from_json5, Ores};
+use super::preset::WorldPreset;
+use super::pos_to_range;
+/// Thickness of the bedrock floor and ceiling
+const BEDROCK_H: i32 = 3;
+/// Caverns open below this height are flooded with magma
+const MAGMA_LEVEL: i32 = 32;
+/// Cavern noise above which the rock is carved, in the middle of the realm
+const CAVERN_THRESHOLD: f32 = 0.5;
+/// Added to the threshold at the floor and the ceiling so that caverns close up towards them
+const CAVERN_FALLOFF: f32 = 0.3;
+const NETHER_SALT: i32 = 0x6e7;
+
+/// Basalt caverns enclosed between a bedrock floor and ceiling, over a sea of magma
+pub struct NetherGen {
+    ores: Ores,
+    seed: i32,
+}
+
+impl NetherGen {
+    pub fn new(preset: &WorldPreset) -> Self {
+        NetherGen {
+            ores: ores_from_json5("assets/gen/ores.json5").unwrap(),
+            seed: preset.seed() as i32 ^ NETHER_SALT,
+        }
+    }
+
+    /// Ragged bedrock `depth` blocks from the floor or the ceiling, None past the bedrock
+    fn bedrock(&self, pos: BlockPos, depth: i32) -> Option<Block> {
+        if depth >= BEDROCK_H {
+            return None;
+        }
+        Some(if depth == 0 || pos.prng(self.seed) % BEDROCK_H as usize >= depth as usize {
+            Block::Bedrock
+        } else {
+            Block::Basalt
+        })
+    }
+}
+
+impl WorldGenerator for NetherGen {
+    /// Fills the whole column at once, the caverns are the surface of the realm
+    fn gen(&self, world: &VoxelWorld, col: ColPos) {
+        let (low, high) = (BEDROCK_H, MAX_HEIGHT as i32 - BEDROCK_H - 1);
+        let gen_span = info_span!("cavern noise gen", name = "cavern noise gen").entered();
+        let [z_range, x_range] = pos_to_range(col);
+        let mut n = NoiseSource::new([z_range, low..=high, x_range], self.seed, 1);
+        let cavern = (n.simplex(1.5) + n.simplex(5.) * 0.3 + n.simplex(20.) * 0.1).normalize();
+        gen_span.exit();
+        let fill_span = info_span!("cavern filling", name = "cavern filling").entered();
+        for cy in 0..Y_CHUNKS as i32 {
+            let chunk_pos = ChunkPos { x: col.x, y: cy, z: col.z, realm: col.realm };
+            let mut chunk = world.chunks.entry(chunk_pos).or_insert_with(TrackedChunk::new);
+            for (dx, dy, dz) in iproduct!(0..CHUNK_S1, 0..CHUNK_S1, 0..CHUNK_S1) {
+                let y = cy * CHUNK_S1I + dy as i32;
+                let pos = BlockPos::from((col, (dx, y, dz)));
+                let block = if let Some(bedrock) = self.bedrock(pos, y.min(MAX_HEIGHT as i32 - 1 - y)) {
+                    bedrock
+                } else {
+                    // -1 at the floor, 1 at the ceiling
+                    let rel = (y - low) as f32 / (high - low) as f32 * 2. - 1.;
+                    if cavern[[dx, (y - low) as usize, dz]] < CAVERN_THRESHOLD + CAVERN_FALLOFF * rel * rel {
+                        Block::Basalt
+                    } else if y <= MAGMA_LEVEL {
+                        Block::Magma
+                    } else {
+                        continue;
+                    }
+                };
+                chunk.set((dx, dy, dz), block);
+            }
+        }
+        fill_span.exit();
+        let mut climate = ColClimate::new();
+        for (dx, dz) in iproduct!(0..CHUNK_S1, 0..CHUNK_S1) {
+            climate.set((dx, dz), Climate::new(1., 0., 0.5, Biome::Desert));
+        }
+        world.set_climate(col, climate);
+    }
+
+    fn gen_underground(&self, world: &VoxelWorld, col: ColPos) {
+        let ore_span = info_span!("ore gen", name = "ore gen").entered();
+        gen_ores(&self.ores, world, col, self.seed, |_, _| (1., 0.));
+        ore_span.exit();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Block;
+    use crate::world::{BlockPos, ColPos, Realm, VoxelWorld, MAX_HEIGHT};
+    use crate::gen::{generator::WorldGenerator, preset::WorldPreset};
+    use super::{NetherGen, MAGMA_LEVEL};
+
+    #[test]
+    fn enclosed_caverns() {
+        let gen = NetherGen::new(&WorldPreset { seed: Some(3), ..Default::default() });
+        let world = VoxelWorld::new();
+        let col = ColPos { x: 2, z: -1, realm: Realm::Nether };
+        gen.gen(&world, col);
+        gen.gen_underground(&world, col);
+        let block = |y: i32| world.get_block(BlockPos::from((col, (10, y, 20))));
+        assert_eq!(block(0), Block::Bedrock);
+        assert_eq!(block(MAX_HEIGHT as i32 - 1), Block::Bedrock);
+        let column: Vec<_> = (0..MAX_HEIGHT as i32).map(block).collect();
+        assert!(column.iter().any(|block| *block == Block::Basalt));
+        // open spaces below the magma level are flooded, none above it
+        assert!(column[..=MAGMA_LEVEL as usize].iter().all(|block| *block != Block::Air));
+        assert!(column[MAGMA_LEVEL as usize + 1..].iter().all(|block| *block != Block::Magma));
+    }
+}
@@ -0,0 +1,124 @@
+use std::{fs, ops::Range};
+use anyhow::Result;
+use serde::Deserialize;
+use crate::Block;
+use crate::world::{BlockPos, ColPos, Realm, VoxelWorld, CHUNK_S1};
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum VeinShape {
+    /// Roughly spherical cluster
+    Blob,
+    /// Flat horizontal sheet, 1 block thick
+    Layer,
+    /// Thin line going in a random direction
+    Streak,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct OreConditions {
+    pub temp: Option<Range<f32>>,
+    pub hum: Option<Range<f32>>,
+}
+
+impl OreConditions {
+    pub fn matches(&self, temp: f32, hum: f32) -> bool {
+        self.temp.as_ref().map_or(true, |range| range.contains(&temp))
+            && self.hum.as_ref().map_or(true, |range| range.contains(&hum))
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct OreVein {
+    pub ore: Block,
+    /// Blocks that can be replaced by the ore
+    pub hosts: Vec<Block>,
+    pub min_y: i32,
+    pub max_y: i32,
+    pub vein: VeinShape,
+    /// Approximate number of blocks in a vein
+    pub size: u32,
+    /// Average number of veins per column
+    pub frequency: f32,
+    #[serde(default)]
+    pub conditions: OreConditions,
+    /// Realm where the vein appears
+    #[serde(default)]
+    pub realm: Realm,
+}
+
+pub type Ores = Vec<OreVein>;
+
+pub fn ores_from_json5(path: &str) -> Result<Ores> {
+    Ok(json5::from_str(&fs::read_to_string(path)?)?)
+}
+
+const DIRS: [(i32, i32, i32); 6] = [(1, 0, 0), (-1, 0, 0), (0, 1, 0), (0, -1, 0), (0, 0, 1), (0, 0, -1)];
+
+fn replace_host(world: &VoxelWorld, col: ColPos, pos: BlockPos, hosts: &[Block], ore: Block) {
+    // veins are cut at column borders so that the result doesn't depend on generation order
+    let (pos_col, _): (ColPos, _) = pos.into();
+    if pos_col == col && hosts.contains(&world.get_block_safe(pos)) {
+        world.set_block(pos, ore);
+    }
+}
+
+impl OreVein {
+    /// Number of veins to place in a column, the fractional part of frequency is used as a probability
+    fn count(&self, rng: usize) -> u32 {
+        let extra = ((rng & 0xff) as f32 / 256.) < self.frequency.fract();
+        self.frequency as u32 + extra as u32
+    }
+
+    fn grow(&self, world: &VoxelWorld, col: ColPos, start: BlockPos, seed: i32) {
+        let mut pos = start;
+        let rng = start.prng(seed);
+        match self.vein {
+            VeinShape::Blob => for i in 0..self.size {
+                replace_host(world, col, pos, &self.hosts, self.ore);
+                let (dx, dy, dz) = DIRS[(pos.prng(seed) ^ i as usize) % DIRS.len()];
+                // stay close to the center so that blobs stay compact
+                pos = if pos.dist(start) > 2 { start } else { pos + (dx, dy, dz) };
+            },
+            VeinShape::Layer => {
+                let radius = (self.size as f32).sqrt() as i32 / 2;
+                for (dx, dz) in itertools::iproduct!(-radius..=radius, -radius..=radius) {
+                    if dx * dx + dz * dz <= radius * radius {
+                        replace_host(world, col, start + (dx, 0, dz), &self.hosts, self.ore);
+                    }
+                }
+            },
+            VeinShape::Streak => {
+                let (dx, dy, dz) = DIRS[rng % DIRS.len()];
+                let (sx, _, sz) = DIRS[(rng >> 3) % DIRS.len()];
+                for i in 0..self.size as i32 {
+                    replace_host(world, col, pos, &self.hosts, self.ore);
+                    pos = pos + (dx, dy, dz);
+                    // drift sideways every few blocks so the streak isn't perfectly straight
+                    if i % 3 == 2 {
+                        pos = pos + (sx, 0, sz);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Places every ore vein of the column's realm, `climate` gives the (temperature, humidity) at a position in the column
+pub fn gen_ores(ores: &Ores, world: &VoxelWorld, col: ColPos, seed: i32, climate: impl Fn(usize, usize) -> (f32, f32)) {
+    for (i, ore) in ores.iter().enumerate() {
+        if ore.realm != col.realm {
+            continue;
+        }
+        let col_rng = BlockPos::from((col, (0, i as i32, 0))).prng(seed);
+        for v in 0..ore.count(col_rng) {
+            let rng = BlockPos::from((col, (v as usize, i as i32, 1))).prng(seed);
+            let (dx, dz) = (rng % CHUNK_S1, (rng >> 8) % CHUNK_S1);
+            let (temp, hum) = climate(dx, dz);
+            if !ore.conditions.matches(temp, hum) {
+                continue;
+            }
+            let y = ore.min_y + ((rng >> 16) % (ore.max_y - ore.min_y).max(1) as usize) as i32;
+            ore.grow(world, col, BlockPos::from((col, (dx, y, dz))), seed);
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use std::{collections::HashMap, fs, path::Path};
+use anyhow::{ensure, Result};
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+use crate::world::{Realm, MAX_GEN_HEIGHT, WATER_H};
+use super::generator::{GeneratorRegistry, RealmGenerators};
+
+pub const PRESETS_DIR: &str = "assets/gen/presets";
+pub const WORLDS_DIR: &str = "worlds";
+const PRESET_FILE: &str = "preset.json5";
+/// json5 reads numbers as f64, larger seeds wouldn't survive saving the preset
+const SEED_BITS: u32 = 53;
+
+/// Generation settings of a world, picked from assets/gen/presets when the world is created
+/// and saved in the world folder so it's generated the same way every time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Resource)]
+#[serde(default, deny_unknown_fields)]
+pub struct WorldPreset {
+    /// Picked at random when the world is created if not specified
+    pub seed: Option<u64>,
+    pub sea_level: i32,
+    /// Rough fraction of the surface that is above sea level
+    pub land_ratio: f32,
+    /// Multiplies the height of mountains
+    pub mountain_scale: f32,
+    /// Multiplies the frequency of continents, higher values make smaller continents
+    pub continent_scale: f32,
+    /// Multiplies the frequency of rifts, 0 disables them
+    pub rift_frequency: f32,
+    /// Strength of the overhangs, arches and cliffs carved in mountains and rocky areas, 0 disables them.
+    /// Values above 1 spread them over more of the terrain, they never reach deeper than at 1.
+    pub overhangs: f32,
+    /// Name of the generator used for each realm
+    pub generators: HashMap<Realm, String>,
+}
+
+impl Default for WorldPreset {
+    fn default() -> Self {
+        WorldPreset {
+            seed: None,
+            sea_level: WATER_H,
+            land_ratio: 0.4,
+            mountain_scale: 1.,
+            continent_scale: 1.,
+            rift_frequency: 1.,
+            overhangs: 1.,
+            generators: RealmGenerators::default().0,
+        }
+    }
+}
+
+impl WorldPreset {
+    pub fn from_json5(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(json5::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn validate(&self, registry: &GeneratorRegistry) -> Result<()> {
+        ensure!(
+            self.sea_level > 0 && self.sea_level < MAX_GEN_HEIGHT as i32,
+            "sea_level must be between 1 and {}, got {}", MAX_GEN_HEIGHT - 1, self.sea_level
+        );
+        ensure!((0. ..=1.).contains(&self.land_ratio), "land_ratio must be between 0 and 1, got {}", self.land_ratio);
+        ensure!(self.mountain_scale >= 0., "mountain_scale can't be negative, got {}", self.mountain_scale);
+        ensure!(self.continent_scale > 0., "continent_scale must be positive, got {}", self.continent_scale);
+        ensure!(self.rift_frequency >= 0., "rift_frequency can't be negative, got {}", self.rift_frequency);
+        ensure!(self.overhangs >= 0., "overhangs can't be negative, got {}", self.overhangs);
+        for (realm, name) in self.generators.iter() {
+            ensure!(registry.contains(name), "unknown generator '{name}' for {realm:?}");
+        }
+        Ok(())
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed.unwrap_or_default()
+    }
+
+    fn random_seed() -> u64 {
+        rand::random::<u64>() >> (u64::BITS - SEED_BITS)
+    }
+
+    /// Loads the preset saved with the world, or creates the world from the named preset
+    pub fn load_or_create(world_name: &str, preset_name: &str, registry: &GeneratorRegistry) -> Result<Self> {
+        let world_dir = Path::new(WORLDS_DIR).join(world_name);
+        let saved_path = world_dir.join(PRESET_FILE);
+        if saved_path.exists() {
+            let preset = WorldPreset::from_json5(&saved_path)?;
+            preset.validate(registry)?;
+            return Ok(preset);
+        }
+        let mut preset = WorldPreset::from_json5(Path::new(PRESETS_DIR).join(format!("{preset_name}.json5")))?;
+        preset.validate(registry)?;
+        preset.seed = Some(preset.seed.unwrap_or_else(WorldPreset::random_seed));
+        fs::create_dir_all(&world_dir)?;
+        fs::write(saved_path, json5::to_string(&preset)?)?;
+        Ok(preset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use crate::gen::generator::GeneratorRegistry;
+    use super::{WorldPreset, PRESETS_DIR, SEED_BITS};
+
+    #[test]
+    fn presets_are_valid() {
+        let registry = GeneratorRegistry::new();
+        for entry in fs::read_dir(PRESETS_DIR).unwrap() {
+            let path = entry.unwrap().path();
+            let preset = WorldPreset::from_json5(&path).unwrap();
+            assert!(preset.validate(&registry).is_ok(), "{path:?} is not valid");
+        }
+    }
+
+    #[test]
+    fn invalid_preset() {
+        let registry = GeneratorRegistry::new();
+        let preset: WorldPreset = json5::from_str("{ land_ratio: 1.5 }").unwrap();
+        assert!(preset.validate(&registry).is_err());
+        let preset: WorldPreset = json5::from_str("{ generators: { Overworld: 'nope' } }").unwrap();
+        assert!(preset.validate(&registry).is_err());
+        assert!(json5::from_str::<WorldPreset>("{ sea_levle: 40 }").is_err());
+    }
+
+    #[test]
+    fn seed_round_trip() {
+        let seeds = (0..64).map(|_| WorldPreset::random_seed()).chain([(1 << SEED_BITS) - 1]);
+        for seed in seeds {
+            let preset = WorldPreset { seed: Some(seed), ..Default::default() };
+            let saved = json5::to_string(&preset).unwrap();
+            assert_eq!(json5::from_str::<WorldPreset>(&saved).unwrap(), preset, "{saved}");
+        }
+    }
+}
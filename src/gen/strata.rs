@@ -0,0 +1,90 @@
+use std::{fs, ops::Range};
+use anyhow::Result;
+use serde::Deserialize;
+use crate::Block;
+use crate::world::{ChunkPos, ColPos, VoxelWorld, CHUNK_S1, CHUNK_S1I, Y_CHUNKS};
+use super::ores::OreConditions;
+
+/// A layer of rock replacing the default underground rock where its conditions are met
+#[derive(Debug, Deserialize, Clone)]
+pub struct Stratum {
+    pub rock: Block,
+    /// Height range of the layer, its boundaries are warped by noise
+    pub min_y: i32,
+    pub max_y: i32,
+    /// Range of the regional noise where the layer appears, regions span a few hundred blocks
+    #[serde(default = "full_region")]
+    pub region: Range<f32>,
+    /// Range of the terrain height above, relative to sea level
+    #[serde(default)]
+    pub surface: Option<Range<i32>>,
+    #[serde(default)]
+    pub conditions: OreConditions,
+}
+
+fn full_region() -> Range<f32> {
+    0. ..1.
+}
+
+pub type Strata = Vec<Stratum>;
+
+pub fn strata_from_json5(path: &str) -> Result<Strata> {
+    Ok(json5::from_str(&fs::read_to_string(path)?)?)
+}
+
+/// What the strata need to know about a column of blocks
+pub struct RockColumn {
+    /// Height of the terrain relative to sea level
+    pub surface: i32,
+    pub temp: f32,
+    pub hum: f32,
+    /// Regional noise in [0; 1]
+    pub region: f32,
+    /// Vertical shift of the layer boundaries
+    pub warp: i32,
+}
+
+impl Stratum {
+    fn matches_column(&self, column: &RockColumn) -> bool {
+        self.region.contains(&column.region)
+            && self.surface.as_ref().map_or(true, |range| range.contains(&column.surface))
+            && self.conditions.matches(column.temp, column.hum)
+    }
+}
+
+/// Replaces the `default` rock of the column with the first stratum matching each block
+pub fn gen_strata(strata: &Strata, world: &VoxelWorld, col: ColPos, default: Block, column: impl Fn(usize, usize) -> RockColumn) {
+    for (dx, dz) in itertools::iproduct!(0..CHUNK_S1, 0..CHUNK_S1) {
+        let column = column(dx, dz);
+        let candidates: Vec<_> = strata.iter().filter(|stratum| stratum.matches_column(&column)).collect();
+        if candidates.is_empty() {
+            continue;
+        }
+        for cy in 0..Y_CHUNKS as i32 {
+            let chunk_pos = ChunkPos { x: col.x, y: cy, z: col.z, realm: col.realm };
+            let Some(mut chunk) = world.chunks.get_mut(&chunk_pos) else {
+                continue;
+            };
+            for dy in 0..CHUNK_S1 {
+                if *chunk.get((dx, dy, dz)) != default {
+                    continue;
+                }
+                let y = cy * CHUNK_S1I + dy as i32 + column.warp;
+                if let Some(stratum) = candidates.iter().find(|stratum| (stratum.min_y..stratum.max_y).contains(&y)) {
+                    chunk.set((dx, dy, dz), stratum.rock);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::strata_from_json5;
+
+    #[test]
+    fn load_strata() {
+        let strata = strata_from_json5("assets/gen/strata.json5").unwrap();
+        assert!(strata.iter().all(|stratum| stratum.min_y < stratum.max_y && stratum.region.start < stratum.region.end));
+    }
+}
@@ -0,0 +1,158 @@
+use std::{collections::HashMap, fs};
+use anyhow::Result;
+use serde::Deserialize;
+use crate::Block;
+use crate::world::{BlockPos, ColPos, VoxelWorld, CHUNK_S1};
+use super::growables::{outranks, tree_rank};
+
+/// Side of the square regions (in columns) that each hold at most one structure
+const REGION_COLS: i32 = 4;
+/// Probability for a region to hold a structure
+const STRUCTURE_CHANCE: f32 = 0.4;
+const STRUCTURE_SALT: i32 = 0x57c7;
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Placement {
+    /// Sits on the ground, foundations are extended down to the terrain
+    Surface,
+    /// Entirely below the lowest point of the terrain it covers
+    Buried { depth: i32 },
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Structure {
+    pub placement: Placement,
+    /// Relative chance of being picked when a region gets a structure
+    pub weight: f32,
+    /// Block used to fill the gap between the bottom layer and the ground
+    #[serde(default)]
+    pub foundation: Option<Block>,
+    /// Block placed by each character of the layers, spaces leave the terrain untouched
+    pub palette: HashMap<char, Block>,
+    /// Loot table of the containers placed by a character
+    #[serde(default)]
+    pub loot: HashMap<char, String>,
+    /// Horizontal slices from bottom to top, each row goes along x and rows are stacked along z
+    pub layers: Vec<Vec<String>>,
+}
+
+pub type Structures = Vec<Structure>;
+
+/// Loads every structure template of the folder, sorted by file name so the pick stays deterministic
+pub fn structures_from_dir(path: &str) -> Result<Structures> {
+    let mut paths = fs::read_dir(path)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    paths.sort();
+    paths.into_iter()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json5"))
+        .map(|path| Ok(json5::from_str(&fs::read_to_string(path)?)?))
+        .collect()
+}
+
+/// Blocks of the template that trees could place, air included, only replace the trees of any column that grew there
+/// if they outrank them, like overlapping trees do; the others replace everything
+fn place_block(world: &VoxelWorld, pos: BlockPos, block: Block) {
+    if tree_rank(block).is_some() {
+        world.set_block_if(pos, block, |current| tree_rank(current).is_none() || outranks(block, current));
+    } else {
+        world.set_block(pos, block);
+    }
+}
+
+impl Structure {
+    /// Footprint of the structure along x and z
+    fn size(&self) -> (usize, usize) {
+        let size_x = self.layers.iter().flatten().map(|row| row.chars().count()).max().unwrap_or(0);
+        let size_z = self.layers.iter().map(|layer| layer.len()).max().unwrap_or(0);
+        (size_x, size_z)
+    }
+
+    fn blocks(&self) -> impl Iterator<Item = ((usize, i32, usize), char)> + '_ {
+        self.layers.iter().enumerate().flat_map(|(y, layer)|
+            layer.iter().enumerate().flat_map(move |(z, row)|
+                row.chars().enumerate().filter(|(_, c)| *c != ' ').map(move |(x, c)| ((x, y as i32, z), c))
+            )
+        )
+    }
+
+    fn place(&self, world: &VoxelWorld, col: ColPos, (dx, y, dz): (usize, i32, usize), surface: &[[i32; CHUNK_S1]; CHUNK_S1]) {
+        for ((x, ly, z), c) in self.blocks() {
+            let Some(block) = self.palette.get(&c) else {
+                continue;
+            };
+            let pos = BlockPos::from((col, (dx + x, y + ly, dz + z)));
+            if ly == 0 {
+                if let Some(foundation) = self.foundation {
+                    for fy in (surface[dx + x][dz + z] + 1)..y {
+                        place_block(world, BlockPos::from((col, (dx + x, fy, dz + z))), foundation);
+                    }
+                }
+            }
+            place_block(world, pos, *block);
+            if let Some(loot_table) = self.loot.get(&c) {
+                world.tag_container(pos, loot_table);
+            }
+        }
+    }
+}
+
+/// Places the structure of the column's region if the region picked this column.
+/// Structures never cross column borders and trees growing into them from neighbouring columns are resolved
+/// with `place_block`, so the result doesn't depend on generation order.
+pub fn gen_structures(
+    structures: &Structures,
+    world: &VoxelWorld,
+    col: ColPos,
+    seed: i32,
+    sea_level: i32,
+    surface: &[[i32; CHUNK_S1]; CHUNK_S1],
+) {
+    let total_weight: f32 = structures.iter().map(|structure| structure.weight).sum();
+    if total_weight <= 0. {
+        return;
+    }
+    let region = ColPos {
+        x: col.x.div_euclid(REGION_COLS),
+        z: col.z.div_euclid(REGION_COLS),
+        realm: col.realm,
+    };
+    let rng = region.prng(seed ^ STRUCTURE_SALT);
+    if (rng & 0xff) as f32 / 256. >= STRUCTURE_CHANCE {
+        return;
+    }
+    let picked_col = (
+        region.x * REGION_COLS + ((rng >> 8) % REGION_COLS as usize) as i32,
+        region.z * REGION_COLS + ((rng >> 12) % REGION_COLS as usize) as i32,
+    );
+    if picked_col != (col.x, col.z) {
+        return;
+    }
+    let mut pick = ((rng >> 16) & 0xffff) as f32 / 65536. * total_weight;
+    let Some(structure) = structures.iter().find(|structure| {
+        pick -= structure.weight;
+        pick < 0.
+    }) else {
+        return;
+    };
+    let (size_x, size_z) = structure.size();
+    if size_x == 0 || size_x > CHUNK_S1 || size_z > CHUNK_S1 {
+        return;
+    }
+    let col_rng = col.prng(seed ^ STRUCTURE_SALT);
+    let dx = col_rng % (CHUNK_S1 - size_x + 1);
+    let dz = (col_rng >> 8) % (CHUNK_S1 - size_z + 1);
+    let ground = surface[dx..dx + size_x].iter().flat_map(|row| &row[dz..dz + size_z]);
+    let (min_y, sum_y) = ground.fold((i32::MAX, 0), |(min_y, sum_y), y| (min_y.min(*y), sum_y + y));
+    let y = match structure.placement {
+        Placement::Surface => {
+            let mean_y = sum_y / (size_x * size_z) as i32;
+            if mean_y <= sea_level {
+                return;
+            }
+            mean_y + 1
+        },
+        Placement::Buried { depth } => min_y - depth - structure.layers.len() as i32,
+    };
+    structure.place(world, col, (dx, y, dz), surface);
+}
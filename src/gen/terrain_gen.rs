@@ -1,28 +1,48 @@
-use crate::gen::earth_gen::Earth;
+use crate::gen::generator::{GeneratorRegistry, RealmGenerators};
+use crate::gen::preset::WorldPreset;
 use crate::world::VoxelWorld;
-use crate::WorldRng;
 use bevy::ecs::system::Res;
 use bevy::tasks::AsyncComputeTaskPool;
-use std::collections::HashMap;
+use std::collections::VecDeque;
 use std::sync::Arc;
 use std::thread::yield_now;
 use crate::world::LoadOrders;
 
-pub fn setup_gen_thread(blocks: Res<VoxelWorld>, world_rng: Res<WorldRng>, load_orders: Res<LoadOrders>) {
+pub fn setup_gen_thread(
+    blocks: Res<VoxelWorld>,
+    load_orders: Res<LoadOrders>,
+    preset: Res<WorldPreset>,
+) {
     let thread_pool = AsyncComputeTaskPool::get();
-    let chunks = Arc::clone(&blocks.chunks);
-    let seed_value = world_rng.seed;
+    let world = blocks.clone();
+    let preset = preset.clone();
     let load_orders = Arc::clone(&load_orders.to_generate);
+    let realm_gens = RealmGenerators(preset.generators.clone()).with_env_override();
     thread_pool.spawn(
         async move {
-            let gen = Earth::new(seed_value as u32, HashMap::new());
-            let world = VoxelWorld::new_with(chunks);
+            let gens = realm_gens.create(&GeneratorRegistry::new(), &preset);
+            // surfaces are generated first, underground layers only when there's no surface left to generate
+            let mut underground_orders = VecDeque::new();
             loop {
-                let Some((col_pos, _)) = load_orders.try_write_arc().and_then(|mut ld| ld.pop()) else {
+                if let Some((col_pos, _)) = load_orders.try_write_arc().and_then(|mut ld| ld.pop()) {
+                    if let Some(gen) = gens.get(&col_pos.realm) {
+                        gen.gen(&world, col_pos);
+                    }
+                    world.mark_change_col(col_pos);
+                    underground_orders.push_back(col_pos);
+                    continue;
+                }
+                let Some(col_pos) = underground_orders.pop_front() else {
                     yield_now();
                     continue;
                 };
-                gen.gen(&world, col_pos);
+                // the column may have been unloaded in the meantime
+                if !world.has_col(col_pos) {
+                    continue;
+                }
+                if let Some(gen) = gens.get(&col_pos.realm) {
+                    gen.gen_underground(&world, col_pos);
+                }
                 world.mark_change_col(col_pos);
             }
         }
@@ -1,11 +1,12 @@
-use std::ops::Range;
+use std::{ops::Range, str::FromStr};
 use serde::Deserialize;
 use strum_macros::EnumString;
 use crate::gen::growables::*;
 use crate::world::{BlockPos, VoxelWorld};
+use crate::Block;
 
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Deserialize)]
 #[derive(EnumString)]
 pub enum Tree {
     Oak,
@@ -19,22 +20,24 @@ pub enum Tree {
     Baobab,
     Cactus,
     Acacia,
-    Bamboo
+    Bamboo,
+    // Aether species
+    Skyroot,
+    Cloudpine,
+    Willowmist,
 }
 
 impl Tree {
-    pub fn grow(&self, world: &VoxelWorld, pos: BlockPos, seed: i32, dist: f32) {
-        if !world.get_block_safe(pos).is_fertile_soil() { return; }
-        match self {
-            Tree::Spruce => grow_spruce(world, pos, seed, dist),
-            Tree::Birch => grow_birch(world, pos, seed, dist),
-            Tree::Cypress => grow_cypress(world, pos, seed, dist),
-            Tree::Oak | Tree::Chestnut | Tree::Ironwood => grow_oak(world, pos, seed, dist),
-            Tree::Acacia => grow_acacia(world, pos, seed, dist),
-            Tree::Sequoia => grow_sequoia(world, pos, seed, dist),
-            Tree::Palm | Tree::Baobab => grow_baobab(world, pos, seed, dist),
-            _ => {}
-        }
+    /// The tree a sapling grows into, saplings are named after their wood
+    pub fn from_sapling(block: Block) -> Option<Self> {
+        Tree::from_str(block.to_string().strip_suffix("Sapling")?).ok()
+    }
+
+    /// Grows the tree on the soil block at `pos` if the species can grow on it
+    pub fn grow(&self, shapes: &TreeShapes, world: &VoxelWorld, pos: BlockPos, seed: i32, dist: f32) {
+        let Some(shape) = shapes.get(self) else { return; };
+        if !shape.can_grow_on(world.get_block_safe(pos)) { return; }
+        shape.grow(world, pos, seed, dist);
     }
 }
 
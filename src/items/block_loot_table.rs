@@ -7,15 +7,18 @@ use super::item::{Item, ToolKind};
 enum DropKind {
     #[serde(rename = "Self")]
     Itself,
+    /// The sapling matching the broken leaves
+    Sapling,
     #[serde(untagged)]
     Item(Item),
 }
 
-impl Into<Item> for (DropKind, Block) {
-    fn into(self) -> Item {
+impl Into<Option<Item>> for (DropKind, Block) {
+    fn into(self) -> Option<Item> {
         match self.0 {
-            DropKind::Itself => Item::Block(self.1),
-            DropKind::Item(item) => item,
+            DropKind::Itself => Some(Item::Block(self.1)),
+            DropKind::Sapling => self.1.sapling().map(Item::Block),
+            DropKind::Item(item) => Some(item),
         }
     }
 }
@@ -84,7 +87,7 @@ impl From<(LootEntryPartial, Block)> for LootEntry {
         Self {
             hardness: entry.hardness,
             quantity: if entry.drops.is_none() { None } else { Some(entry.quantity()) },
-            drops: entry.drops.map(|drop| (drop, block).into()),
+            drops: entry.drops.and_then(|drop| (drop, block).into()),
         }
     }
 }
@@ -166,4 +169,19 @@ mod tests {
         assert_eq!(block_looting.get(Some(&Item::Stick), &Block::Cobblestone).drops, Some(Item::Rock));
         assert_eq!(block_looting.get(None, &Block::Cobblestone).drops, Some(Item::Rock));
     }
+
+    #[test]
+    fn sapling_drops() {
+        let config = r#"
+        {
+            Default: {
+                Leaves: { hardness: 1, drops: "Sapling" },
+                Plant: { hardness: 0.2, drops: "Sapling" },
+            }
+        }
+        "#;
+        let block_looting: BlockLootTable = json5::from_str(config).unwrap();
+        assert_eq!(block_looting.get(None, &Block::OakLeaves).drops, Some(Item::Block(Block::OakSapling)));
+        assert_eq!(block_looting.get(None, &Block::Grass).drops, None);
+    }
 }
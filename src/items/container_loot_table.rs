@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+use bevy::prelude::Resource;
+use rand::Rng;
+use serde::Deserialize;
+use super::{Item, Stack};
+
+fn default_quantity() -> u32 {
+    1
+}
+
+fn default_chance() -> f32 {
+    1.
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LootRoll {
+    pub item: Item,
+    #[serde(default = "default_quantity")]
+    pub min: u32,
+    #[serde(default = "default_quantity")]
+    pub max: u32,
+    /// Probability for the item to be in the container at all
+    #[serde(default = "default_chance")]
+    pub chance: f32,
+}
+
+/// Loot of generated containers, by loot table ID
+#[derive(Debug, Resource, Deserialize)]
+pub struct ContainerLootTable(HashMap<String, Vec<LootRoll>>);
+
+impl ContainerLootTable {
+    pub fn roll(&self, loot_table: &str, rng: &mut impl Rng) -> Vec<Stack> {
+        let Some(rolls) = self.0.get(loot_table) else {
+            return Vec::new();
+        };
+        rolls.iter()
+            .filter(|roll| rng.gen::<f32>() < roll.chance)
+            .map(|roll| Stack::Some(roll.item, rng.gen_range(roll.min..=roll.max.max(roll.min))))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use crate::items::{Item, Stack};
+    use super::ContainerLootTable;
+
+    #[test]
+    fn roll_loot() {
+        let config = r#"
+        {
+            ruin: [
+                { item: "Coal", min: 2, max: 4 },
+                { item: "IronIngot", chance: 0 },
+            ]
+        }
+        "#;
+        let loot_table: ContainerLootTable = json5::from_str(config).unwrap();
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let loot = loot_table.roll("ruin", &mut rng);
+        assert_eq!(loot.len(), 1);
+        let Stack::Some(Item::Coal, quantity) = loot[0] else {
+            panic!("Only coal should be rolled");
+        };
+        assert!((2..=4).contains(&quantity));
+        assert!(loot_table.roll("unknown", &mut rng).is_empty());
+    }
+}
@@ -3,8 +3,10 @@ mod inventory;
 mod block_loot_table;
 mod craft_table;
 mod firing_table;
+mod container_loot_table;
 pub use inventory::*;
 pub use item::*;
 pub use block_loot_table::*;
 pub use craft_table::*;
-pub use firing_table::*;
\ No newline at end of file
+pub use firing_table::*;
+pub use container_loot_table::*;
\ No newline at end of file
@@ -0,0 +1,18 @@
+pub mod asset_processing;
+pub mod block;
+pub mod items;
+pub mod ui;
+pub mod world;
+pub mod render;
+pub mod agents;
+pub mod sounds;
+pub mod gen;
+include!(concat!(env!("OUT_DIR"), "/blocks.rs"));
+use bevy::prelude::Resource;
+use rand_chacha::ChaCha8Rng;
+
+#[derive(Resource)]
+pub struct WorldRng {
+    pub seed: u64,
+    pub rng: ChaCha8Rng
+}
@@ -1,30 +1,21 @@
-mod asset_processing;
-mod block;
-mod items;
-mod ui;
-mod world;
-mod render;
-mod agents;
-mod sounds;
-mod gen;
-include!(concat!(env!("OUT_DIR"), "/blocks.rs"));
 use bevy::{image::{ImageAddressMode, ImageFilterMode, ImageSamplerDescriptor}, prelude::*};
-use world::VoxelWorld;
 use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
-use sounds::SoundPlugin;
-use ui::UIPlugin;
-use render::{Render, TextureLoadPlugin};
-use agents::{MovementPlugin, PlayerPlugin};
-use world::GenPlugin;
-const SEED: u64 = 42;
-
-#[derive(Resource)]
-pub struct WorldRng {
-    pub seed: u64,
-    pub rng: ChaCha8Rng
-}
+use riverbed::{
+    agents::{MovementPlugin, PlayerPlugin},
+    gen::{GeneratorRegistry, WorldPreset},
+    render::{Render, TextureLoadPlugin},
+    sounds::SoundPlugin,
+    ui::UIPlugin,
+    world::{GenPlugin, VoxelWorld},
+    WorldRng,
+};
 
 fn main() {
+    let world_name = std::env::var("RIVERBED_WORLD").unwrap_or("world".to_string());
+    let preset_name = std::env::var("RIVERBED_PRESET").unwrap_or("default".to_string());
+    let preset = WorldPreset::load_or_create(&world_name, &preset_name, &GeneratorRegistry::new())
+        .unwrap_or_else(|err| panic!("Couldn't load world '{world_name}' with preset '{preset_name}': {err}"));
+    let seed = preset.seed();
     let mut app = App::new();
 
     app
@@ -49,9 +40,10 @@ fn main() {
             })
         )
         .insert_resource(WorldRng {
-            seed: SEED,
-            rng: ChaCha8Rng::seed_from_u64(SEED)
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed)
         })
+        .insert_resource(preset)
         .add_plugins(PlayerPlugin)
         .add_plugins(TextureLoadPlugin)
         .add_plugins(UIPlugin)
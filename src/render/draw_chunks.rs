@@ -11,13 +11,14 @@ use itertools::{iproduct, Itertools};
 use strum::IntoEnumIterator;
 use crate::block::Face;
 use crate::world::pos2d::chunks_in_col;
-use crate::world::{VoxelWorld, ChunkPos, CHUNK_S1, Y_CHUNKS};
+use crate::world::{VoxelWorld, ChunkPos, ColPos, CHUNK_S1, Y_CHUNKS};
 use crate::world::{range_around, ColUnloadEvent, PlayerArea, LoadAreaAssigned};
 use super::chunk_culling::chunk_culling;
 use super::shared_load_area::{setup_shared_load_area, update_shared_load_area, SharedLoadArea};
 use super::texture_array::BlockTextureArray;
 use super::BlockTexState;
 use super::texture_array::{TextureMap, TextureArrayPlugin};
+use super::tint::Tints;
 const GRID_GIZMO_LEN: i32 = 4;
 
 #[derive(Debug, Component)]
@@ -81,6 +82,8 @@ pub struct MeshReciever(Receiver<(Option<Mesh>, ChunkPos, Face, LOD)>);
 fn setup_mesh_thread(mut commands: Commands, blocks: Res<VoxelWorld>, shared_load_area: Res<SharedLoadArea>, texture_map: Res<TextureMap>) {
     let thread_pool = AsyncComputeTaskPool::get();
     let chunks = Arc::clone(&blocks.chunks);
+    let climates = Arc::clone(&blocks.climates);
+    let tints = Tints::from_csvs().unwrap();
     let (mesh_sender, mesh_reciever) = unbounded();
     commands.insert_resource(MeshReciever(mesh_reciever));
     let shared_load_area = Arc::clone(&shared_load_area.0);
@@ -99,7 +102,8 @@ fn setup_mesh_thread(mut commands: Commands, blocks: Res<VoxelWorld>, shared_loa
                 let Some(chunk) = chunks.get(&chunk_pos) else {
                     continue;
                 };
-                let face_meshes = chunk.create_face_meshes(&*texture_map, lod);
+                let climate = climates.get(&ColPos::from(chunk_pos));
+                let face_meshes = chunk.create_face_meshes(&*texture_map, climate.as_deref(), &tints, lod);
                 for (i, face_mesh) in face_meshes.into_iter().enumerate() {
                     let face = i.into();
                     if mesh_sender.send((face_mesh, chunk_pos, face, LOD(lod))).is_err() {
@@ -8,9 +8,10 @@ use bevy::{
 };
 use binary_greedy_meshing as bgm;
 
-use crate::{Block, block::Face, world::{pad_linearize, Chunk, CHUNKP_S3}};
+use crate::{Block, block::Face, world::{pad_linearize, Chunk, Climate, ColClimate, CHUNKP_S3}};
 use crate::world::CHUNK_S1;
 use super::texture_array::TextureMapTrait;
+use super::tint::Tints;
 
 const MASK_6: u64 = 0b111111;
 const MASK_XYZ: u64 = 0b111111_111111_111111;
@@ -54,7 +55,13 @@ impl Chunk {
 
     /// Doesn't work with lod > 2, because chunks are of size 62 (to get to 64 with padding) and 62 = 2*31
     /// TODO: make it work with lod > 2 if necessary (by truncating quads)
-    pub fn create_face_meshes(&self, texture_map: impl TextureMapTrait, lod: usize) ->  [Option<Mesh>; 6] {
+    pub fn create_face_meshes(
+        &self, 
+        texture_map: impl TextureMapTrait, 
+        climate: Option<&ColClimate>, 
+        tints: &Tints, 
+        lod: usize
+    ) ->  [Option<Mesh>; 6] {
         // Gathering binary greedy meshing input data
         let mesh_data_span = info_span!("mesh voxel data", name = "mesh voxel data").entered();
         let voxels = self.voxel_data_lod(lod);
@@ -81,10 +88,15 @@ impl Chunk {
                 let xyz = MASK_XYZ & quad;
                 let block = self.palette[voxel_i];
                 let layer = texture_map.get_texture_index(block, face) as u32;
+                // tints are picked from the climate at the quad's origin
+                let quad_climate = || climate.map_or(Climate::default(), |climate| climate.get((
+                    ((xyz & MASK_6) as usize*lod).min(CHUNK_S1-1), 
+                    (((xyz >> 12) & MASK_6) as usize*lod).min(CHUNK_S1-1)
+                )));
                 let color = match (block, face) {
-                    (Block::GrassBlock, Face::Up) => 0b011_111_001,
-                    (Block::SeaBlock, _) => 0b110_011_001,
-                    (block, _) if block.is_foliage() => 0b010_101_001,
+                    (Block::GrassBlock, Face::Up) | (Block::Grass, _) => tints.grass(&quad_climate()),
+                    (Block::SeaBlock, _) => tints.water(&quad_climate()),
+                    (block, _) if block.is_foliage() => tints.foliage(&quad_climate()),
                     _ => 0b111_111_111
                 };
                 let vertices = face.vertices_packed(xyz as u32, w as u32, h as u32, lod as u32);
@@ -8,6 +8,7 @@ mod texture_array;
 mod sky;
 mod shared_load_area;
 mod effects;
+mod tint;
 use bevy::prelude::Plugin;
 pub use texture_load::*;
 pub use camera::{FpsCam, CameraSpawn};
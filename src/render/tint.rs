@@ -0,0 +1,74 @@
+use std::str::FromStr;
+use anyhow::{anyhow, Result};
+use riverbed_closest::{points::{self, PointIndex}, ClosestTrait};
+use crate::world::Climate;
+
+/// Color in the 9 bits format expected by the chunk shader: `0bbbb_ggg_rrr`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Color9(pub u32);
+
+impl Color9 {
+    pub const WHITE: Color9 = Color9(0b111_111_111);
+}
+
+impl FromStr for Color9 {
+    type Err = anyhow::Error;
+
+    /// Parses a 3 digits hex color such as `693` or `#1AE`
+    fn from_str(s: &str) -> Result<Self> {
+        let digits = s.trim().trim_start_matches('#');
+        let [r, g, b]: [u32; 3] = digits.chars()
+            .map(|c| c.to_digit(16).ok_or(anyhow!("invalid hex color '{}'", s)))
+            .collect::<Result<Vec<_>>>()?
+            .try_into()
+            .map_err(|_| anyhow!("expected 3 hex digits, got '{}'", s))?;
+        // 4 bits per channel to 3 bits per channel
+        Ok(Color9(((b >> 1) << 6) | ((g >> 1) << 3) | (r >> 1)))
+    }
+}
+
+/// Climate driven colors for tinted blocks, indexed by (temperature, humidity)
+pub struct Tints {
+    grass: PointIndex<2, Color9>,
+    foliage: PointIndex<2, Color9>,
+    water: PointIndex<2, Color9>,
+}
+
+impl Tints {
+    pub fn from_csvs() -> Result<Self> {
+        Ok(Tints {
+            grass: points::from_csv("assets/gen/grass_color.csv")?.into(),
+            foliage: points::from_csv("assets/gen/foliage_color.csv")?.into(),
+            water: points::from_csv("assets/gen/water_color.csv")?.into(),
+        })
+    }
+
+    fn pick(colors: &PointIndex<2, Color9>, climate: &Climate) -> u32 {
+        colors.closest([climate.temp(), climate.hum()]).0.0
+    }
+
+    pub fn grass(&self, climate: &Climate) -> u32 {
+        Tints::pick(&self.grass, climate)
+    }
+
+    pub fn foliage(&self, climate: &Climate) -> u32 {
+        Tints::pick(&self.foliage, climate)
+    }
+
+    pub fn water(&self, climate: &Climate) -> u32 {
+        Tints::pick(&self.water, climate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_color9() {
+        assert_eq!(Color9::from_str("fff").unwrap(), Color9::WHITE);
+        assert_eq!(Color9::from_str("#1AE").unwrap(), Color9(0b111_101_000));
+        assert_eq!(Color9::from_str(" 693").unwrap(), Color9(0b001_100_011));
+        assert!(Color9::from_str("69").is_err());
+    }
+}
@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+use crate::agents::Container;
+use super::{game_menu::despawn_screen, ui_tex_map::{UiSlotKind, UiTextureMap}, GameUiState, ItemHolder, UISlot};
+
+const SLOTS_PER_ROW: usize = 9;
+
+pub struct ContainerMenuPlugin;
+
+impl Plugin for ContainerMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(OpenContainer(None))
+            .add_systems(OnEnter(GameUiState::ContainerMenu), open_container_menu)
+            .add_systems(OnExit(GameUiState::ContainerMenu), despawn_screen::<ContainerMenu>)
+            ;
+    }
+}
+
+#[derive(Resource)]
+pub struct OpenContainer(pub Option<Entity>);
+
+#[derive(Component)]
+struct ContainerMenu;
+
+fn open_container_menu(
+    mut commands: Commands,
+    tex_map: Res<UiTextureMap>,
+    open_container: Res<OpenContainer>,
+    container_query: Query<(&Container, &ItemHolder)>,
+) {
+    let Some(container_entt) = open_container.0 else {
+        return;
+    };
+    let Ok((container, ItemHolder::Inventory(slots))) = container_query.get(container_entt) else {
+        return;
+    };
+    commands.spawn((
+        Node {
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            width: Val::Percent(50.),
+            left: Val::VMin(5.),
+            top: Val::VMin(5.),
+            ..Default::default()
+        },
+        BackgroundColor(Color::LinearRgba(LinearRgba::new(0., 0., 0., 0.9))),
+    ))
+    .with_children(
+        |parent| {
+            parent.spawn((
+                Text::new(&container.name),
+                TextFont {
+                    font_size: 40.,
+                    ..Default::default()
+                },
+                Node {
+                    align_self: AlignSelf::Center,
+                    ..Default::default()
+                }
+            ));
+            for (row, stacks) in slots.chunks(SLOTS_PER_ROW).enumerate() {
+                parent.spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    margin: UiRect::all(Val::Vw(0.2)),
+                    ..Default::default()
+                }).with_children(|node| {
+                    for (i, stack) in stacks.iter().enumerate() {
+                        node
+                            .spawn(Node {
+                                margin: UiRect::all(Val::Vw(0.2)),
+                                ..Default::default()
+                            })
+                            .insert(Interaction::default())
+                            .insert(UISlot(container_entt, row * SLOTS_PER_ROW + i))
+                            .with_children(|node| tex_map.make_item_slot(node, stack, UiSlotKind::Default));
+                    }
+                });
+            }
+    })
+    .insert(ContainerMenu);
+}
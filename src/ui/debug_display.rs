@@ -1,7 +1,7 @@
 use bevy::color::palettes::css;
 use bevy::prelude::*;
 use crate::Block;
-use crate::world::VoxelWorld;
+use crate::world::{BlockPos2d, Realm, VoxelWorld};
 use crate::agents::{PlayerControlled, TargetBlock};
 
 pub struct DebugDisplayPlugin;
@@ -13,6 +13,7 @@ impl Plugin for DebugDisplayPlugin {
             .add_systems(Update, update_entt_display)
             .add_systems(Update, update_pos_display)
             .add_systems(Update, update_block_display)
+            .add_systems(Update, update_climate_display)
             ;
     }
 }
@@ -26,6 +27,9 @@ struct DebugTextBlock;
 #[derive(Component)]
 struct DebugTextEntities;
 
+#[derive(Component)]
+struct DebugTextClimate;
+
 fn setup_debug_display(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands.spawn((
         Node {
@@ -42,6 +46,7 @@ fn setup_debug_display(mut commands: Commands, asset_server: Res<AssetServer>) {
     )).with_children(|parent| {
         parent.spawn((TextSpan::new("p: "), DebugTextPos));
         parent.spawn((TextSpan::new("block: "), DebugTextBlock));
+        parent.spawn((TextSpan::new("biome: "), DebugTextClimate));
         parent.spawn((TextSpan::new("E: "), DebugTextEntities));
     });
 }
@@ -80,4 +85,22 @@ fn update_block_display(
     if let Ok(mut block_text) = block_text_query.get_single_mut() {
         block_text.0 = format!("block: {block:?}\n");
     }
-}
\ No newline at end of file
+}
+
+fn update_climate_display(
+    player_query: Query<(&Transform, &Realm), With<PlayerControlled>>,
+    mut climate_text_query: Query<&mut Text, With<DebugTextClimate>>,
+    world: Res<VoxelWorld>,
+) {
+    let (transform, realm) = player_query.single();
+    let Ok(mut climate_text) = climate_text_query.get_single_mut() else {
+        return;
+    };
+    climate_text.0 = match world.climate_at(BlockPos2d::from((transform.translation, *realm))) {
+        Some(climate) => format!(
+            "biome: {:?} (t {:.2}, h {:.2}, ph {:.2})\n", 
+            climate.biome, climate.temp(), climate.hum(), climate.ph()
+        ),
+        None => "biome: -\n".to_string()
+    };
+}
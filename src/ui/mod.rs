@@ -6,11 +6,14 @@ mod craft_menu;
 mod crosshair;
 mod in_hand;
 mod furnace_menu;
+mod container_menu;
 mod item_slots;
 pub use item_slots::*;
 use craft_menu::CraftMenuPlugin;
 use furnace_menu::FurnaceMenuPlugin;
 pub use furnace_menu::OpenFurnace;
+use container_menu::ContainerMenuPlugin;
+pub use container_menu::OpenContainer;
 use crosshair::setup_crosshair;
 pub use hotbar::SelectedHotbarSlot;
 use debug_display::DebugDisplayPlugin;
@@ -39,6 +42,7 @@ impl Plugin for UIPlugin {
             .add_plugins(CraftMenuPlugin)
             .add_plugins(InHandPlugin)
             .add_plugins(FurnaceMenuPlugin)
+            .add_plugins(ContainerMenuPlugin)
             .add_systems(Startup, setup_ui_actions)
             .add_systems(Startup, setup_crosshair)
             .add_systems(Update, process_ui_actions)
@@ -55,13 +59,14 @@ pub enum GameUiState {
     None,
     InGameMenu,
     CraftingMenu,
-    FurnaceMenu
+    FurnaceMenu,
+    ContainerMenu,
 }
 
 impl GameUiState {
     /// Game states that need the cursor to operate in
     pub fn needs_free_cursor(&self) -> bool {
-        matches!(self, GameUiState::InGameMenu | GameUiState::FurnaceMenu)
+        matches!(self, GameUiState::InGameMenu | GameUiState::FurnaceMenu | GameUiState::ContainerMenu)
     }
 
     /// Game states that only need scrolling to operate in (includes all free cursor state)
@@ -1,4 +1,6 @@
-use itertools::Itertools;
+use std::str::FromStr;
+use anyhow::{bail, Context, Result};
+use itertools::{iproduct, Itertools};
 use packed_uints::PackedUints;
 use crate::Block;
 use super::{pos::{ChunkedPos, ColedPos}, utils::Palette, CHUNKP_S1, CHUNKP_S2, CHUNKP_S3, CHUNK_S1};
@@ -58,6 +60,10 @@ impl Chunk {
         (&self.palette[0], 0)
     }
 
+    pub fn bottom(&self, (x, z): ColedPos) -> Option<usize> {
+        (0..CHUNK_S1).find(|y| self.data.get(pad_linearize(x, *y, z)) > 0)
+    }
+
     pub fn set_if_empty(&mut self, (x, y, z): ChunkedPos, block: Block) -> bool {
         let idx = pad_linearize(x, y, z);
         if self.palette[self.data.get(idx)] != Block::Air {
@@ -66,6 +72,70 @@ impl Chunk {
         self.data.set(idx, self.palette.index(block));
         true
     }
+
+    /// Sets the block if `replaces` accepts the current one
+    pub fn set_if(&mut self, (x, y, z): ChunkedPos, block: Block, replaces: impl Fn(Block) -> bool) -> bool {
+        let idx = pad_linearize(x, y, z);
+        if !replaces(self.palette[self.data.get(idx)]) {
+            return false;
+        }
+        self.data.set(idx, self.palette.index(block));
+        true
+    }
+}
+
+impl Chunk {
+    /// Stable encoding of the chunk's blocks, independent from the palette and from the order of blocks.def.
+    /// Made of runs of identical blocks in y, x, z order, each written as the length of the block name,
+    /// the name and the length of the run (u32 little endian).
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut push_run = |block: Block, count: u32| {
+            let name = block.to_string();
+            bytes.push(name.len() as u8);
+            bytes.extend_from_slice(name.as_bytes());
+            bytes.extend_from_slice(&count.to_le_bytes());
+        };
+        let mut run: Option<(Block, u32)> = None;
+        for (y, x, z) in iproduct!(0..CHUNK_S1, 0..CHUNK_S1, 0..CHUNK_S1) {
+            let block = *self.get((x, y, z));
+            run = match run {
+                Some((run_block, count)) if run_block == block => Some((run_block, count + 1)),
+                Some((run_block, count)) => {
+                    push_run(run_block, count);
+                    Some((block, 1))
+                },
+                None => Some((block, 1)),
+            };
+        }
+        if let Some((block, count)) = run {
+            push_run(block, count);
+        }
+        bytes
+    }
+
+    pub fn decode(mut bytes: &[u8]) -> Result<Self> {
+        let mut chunk = Chunk::new();
+        let mut positions = iproduct!(0..CHUNK_S1, 0..CHUNK_S1, 0..CHUNK_S1);
+        while let Some((&name_len, rest)) = bytes.split_first() {
+            let name_len = name_len as usize;
+            if rest.len() < name_len + 4 {
+                bail!("truncated chunk data");
+            }
+            let name = std::str::from_utf8(&rest[..name_len])?;
+            let block = Block::from_str(name).with_context(|| format!("unknown block '{name}'"))?;
+            let count = u32::from_le_bytes(rest[name_len..name_len + 4].try_into()?);
+            for _ in 0..count {
+                let (y, x, z) = positions.next().context("too many blocks in chunk data")?;
+                chunk.set((x, y, z), block);
+            }
+            bytes = &rest[name_len + 4..];
+        }
+        if positions.next().is_some() {
+            bail!("missing blocks in chunk data");
+        }
+        Ok(chunk)
+    }
 }
 
 impl From<&[Block]> for Chunk {
@@ -87,4 +157,25 @@ impl Chunk {
             palette: palette, 
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Block;
+    use super::Chunk;
+
+    #[test]
+    fn codec_roundtrip() {
+        let mut chunk = Chunk::new();
+        chunk.set((0, 0, 0), Block::Bedrock);
+        chunk.set_yrange((3, 20, 7), 10, Block::Granite);
+        chunk.set((61, 61, 61), Block::OakLeaves);
+        let bytes = chunk.encode();
+        let decoded = Chunk::decode(&bytes).unwrap();
+        assert_eq!(decoded.encode(), bytes);
+        assert_eq!(*decoded.get((3, 15, 7)), Block::Granite);
+        assert_eq!(*decoded.get((61, 61, 61)), Block::OakLeaves);
+        assert_eq!(*decoded.get((1, 0, 0)), Block::Air);
+        assert!(Chunk::decode(&bytes[..bytes.len() - 2]).is_err());
+    }
+}
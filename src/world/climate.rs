@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+use crate::gen::Biome;
+use super::{ColedPos, CHUNK_S1, CHUNK_S2};
+
+/// Climate of a single block column, parameters are quantized to a byte to keep column maps compact
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Climate {
+    temp: u8,
+    hum: u8,
+    ph: u8,
+    pub biome: Biome,
+}
+
+fn quantize(value: f32) -> u8 {
+    (value.clamp(0., 1.) * u8::MAX as f32).round() as u8
+}
+
+fn unquantize(value: u8) -> f32 {
+    value as f32 / u8::MAX as f32
+}
+
+impl Climate {
+    pub fn new(temp: f32, hum: f32, ph: f32, biome: Biome) -> Self {
+        Self { temp: quantize(temp), hum: quantize(hum), ph: quantize(ph), biome }
+    }
+
+    pub fn temp(&self) -> f32 {
+        unquantize(self.temp)
+    }
+
+    pub fn hum(&self) -> f32 {
+        unquantize(self.hum)
+    }
+
+    pub fn ph(&self) -> f32 {
+        unquantize(self.ph)
+    }
+}
+
+impl Default for Climate {
+    fn default() -> Self {
+        Climate::new(0.5, 0.5, 0.5, Biome::Grassland)
+    }
+}
+
+/// 2D climate map of a column, filled by terrain generation and kept alongside the column's chunks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColClimate(Box<[Climate]>);
+
+impl ColClimate {
+    pub fn new() -> Self {
+        ColClimate(vec![Climate::default(); CHUNK_S2].into_boxed_slice())
+    }
+
+    pub fn get(&self, (x, z): ColedPos) -> Climate {
+        self.0[z + x * CHUNK_S1]
+    }
+
+    pub fn set(&mut self, (x, z): ColedPos, climate: Climate) {
+        self.0[z + x * CHUNK_S1] = climate;
+    }
+}
@@ -3,13 +3,17 @@ mod load_orders;
 mod voxel_world;
 mod realm;
 mod chunk;
+mod climate;
 mod pos;
 mod utils;
+mod portal;
 
 pub use realm::*;
 pub use voxel_world::*;
 pub use chunk::*;
+pub use climate::*;
 pub use pos::*;
+pub use portal::*;
 pub use load_area::{PlayerArea, RenderDistance, range_around};
 pub use load_orders::{LoadOrders, ColUnloadEvent, BlockEntities};
 use bevy::{app::Startup, ecs::schedule::{apply_deferred, IntoSystemConfigs, SystemSet}, prelude::{Plugin, Update}};
@@ -0,0 +1,198 @@
+use itertools::iproduct;
+use crate::Block;
+use super::{BlockPos, ColPos, Realm, VoxelWorld, MAX_GEN_HEIGHT, WATER_H};
+
+/// Smallest interior of a portal frame, (width, height)
+const PORTAL_MIN: (i32, i32) = (2, 3);
+/// Largest width or height of a portal interior
+const PORTAL_MAX: i32 = 21;
+
+/// The realm a portal with this frame leads to from `from`, None if the block isn't a portal frame
+pub fn portal_destination(frame: Block, from: Realm) -> Option<Realm> {
+    let realm = match frame {
+        Block::Basalt => Realm::Nether,
+        Block::Glowstone => Realm::Aether,
+        _ => return None,
+    };
+    Some(if from == realm { Realm::Overworld } else { realm })
+}
+
+/// Horizontal distances are this many times shorter in the realm than in the Overworld
+pub fn realm_scale(realm: Realm) -> f32 {
+    match realm {
+        Realm::Nether => 8.,
+        _ => 1.,
+    }
+}
+
+/// The interior of a vertical portal frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PortalShape {
+    /// Bottom block of the interior, at the lowest x or z
+    pub corner: BlockPos,
+    /// Whether the portal spans along x, along z otherwise
+    pub along_x: bool,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl PortalShape {
+    fn step(&self) -> (i32, i32, i32) {
+        if self.along_x { (1, 0, 0) } else { (0, 0, 1) }
+    }
+
+    fn at(&self, i: i32, j: i32) -> BlockPos {
+        let (sx, _, sz) = self.step();
+        self.corner + (sx * i, j, sz * i)
+    }
+
+    pub fn interior(&self) -> impl Iterator<Item = BlockPos> + '_ {
+        iproduct!(0..self.width, 0..self.height).map(|(i, j)| self.at(i, j))
+    }
+
+    /// Blocks of the frame, corners included
+    pub fn frame(&self) -> impl Iterator<Item = BlockPos> + '_ {
+        iproduct!(-1..=self.width, -1..=self.height)
+            .filter(|(i, j)| *i == -1 || *j == -1 || *i == self.width || *j == self.height)
+            .map(|(i, j)| self.at(i, j))
+    }
+
+    /// Finds the empty interior of the frame around `inside`, corners of the frame are optional
+    pub fn find(world: &VoxelWorld, inside: BlockPos, frame: Block) -> Option<Self> {
+        [true, false].into_iter().find_map(|along_x| Self::find_along(world, inside, frame, along_x))
+    }
+
+    fn find_along(world: &VoxelWorld, inside: BlockPos, frame: Block, along_x: bool) -> Option<Self> {
+        let is_empty = |pos: BlockPos| world.get_block_safe(pos) == Block::Air;
+        let (sx, _, sz) = if along_x { (1, 0, 0) } else { (0, 0, 1) };
+        // walks from `pos` in a direction until the frame, returns the last empty block
+        let walk = |mut pos: BlockPos, dir: (i32, i32, i32)| -> Option<BlockPos> {
+            for _ in 0..PORTAL_MAX {
+                let next = pos + dir;
+                if !is_empty(next) {
+                    return (world.get_block_safe(next) == frame).then_some(pos);
+                }
+                pos = next;
+            }
+            None
+        };
+        if !is_empty(inside) {
+            return None;
+        }
+        let bottom = walk(inside, (0, -1, 0))?;
+        let corner = walk(bottom, (-sx, 0, -sz))?;
+        let far = walk(corner, (sx, 0, sz))?;
+        let top = walk(corner, (0, 1, 0))?;
+        let shape = PortalShape {
+            corner,
+            along_x,
+            width: (far.x - corner.x) + (far.z - corner.z) + 1,
+            height: top.y - corner.y + 1,
+        };
+        if shape.width < PORTAL_MIN.0 || shape.height < PORTAL_MIN.1 {
+            return None;
+        }
+        let is_corner = |pos: &BlockPos| {
+            let (i, j) = ((pos.x - corner.x) + (pos.z - corner.z), pos.y - corner.y);
+            (i == -1 || i == shape.width) && (j == -1 || j == shape.height)
+        };
+        let complete = shape.interior().all(is_empty)
+            && shape.frame().filter(|pos| !is_corner(pos)).all(|pos| world.get_block_safe(pos) == frame);
+        complete.then_some(shape)
+    }
+
+    pub fn fill(&self, world: &VoxelWorld, block: Block) {
+        for pos in self.interior() {
+            world.set_block(pos, block);
+        }
+    }
+
+    /// Fills the interior with portal blocks and tags it so that `find_portal` finds it
+    pub fn light(&self, world: &VoxelWorld) {
+        self.fill(world, Block::Portal);
+        world.tag_portal(self.corner);
+    }
+
+    /// Builds a 2x3 portal with its frame on the highest ground of the column out of hazards, clearing some room on each side.
+    /// The portal stands on a platform above sea level if the column has no ground.
+    pub fn build_at(world: &VoxelWorld, (x, z): (i32, i32), realm: Realm, frame: Block) -> Self {
+        let pos = |y: i32| BlockPos { x, y, z, realm };
+        let is_room = |block: Block| block.is_traversable() && block.damage() == 0.;
+        let ground = (1..MAX_GEN_HEIGHT as i32).rev().find(|y| {
+            !world.get_block(pos(*y - 1)).is_traversable()
+                && (0..=PORTAL_MIN.1 + 1).all(|dy| is_room(world.get_block(pos(*y + dy))))
+        });
+        let shape = PortalShape {
+            corner: pos(ground.unwrap_or(WATER_H + 1) + 1),
+            along_x: true,
+            width: PORTAL_MIN.0,
+            height: PORTAL_MIN.1,
+        };
+        // room to step out on both sides, with a floor level with the bottom of the frame
+        for (i, j, side) in iproduct!(-1..=shape.width, -1..=shape.height, [-1, 1]) {
+            let pos = shape.at(i, j) + (0, 0, side);
+            if j > -1 {
+                world.set_block(pos, Block::Air);
+            } else if world.get_block(pos).is_traversable() {
+                world.set_block(pos, frame);
+            }
+        }
+        for pos in shape.frame() {
+            world.set_block(pos, frame);
+        }
+        shape.light(world);
+        shape
+    }
+}
+
+/// Bottom block of the lit portal closest to `center`, within `radius` blocks horizontally
+pub fn find_portal(world: &VoxelWorld, center: BlockPos, radius: i32) -> Option<BlockPos> {
+    let (min, max) = (ColPos::from(center + (-radius, 0, -radius)), ColPos::from(center + (radius, 0, radius)));
+    iproduct!(min.x..=max.x, min.z..=max.z)
+        .flat_map(|(x, z)| world.portals_in(ColPos { x, z, realm: center.realm }))
+        .filter(|pos| (pos.x - center.x).abs() <= radius && (pos.z - center.z).abs() <= radius)
+        // the portal may have been broken since it was lit
+        .filter(|pos| world.get_block(*pos) == Block::Portal)
+        .min_by_key(|pos| ((pos.x - center.x).pow(2) + (pos.z - center.z).pow(2), pos.x, pos.z, pos.y))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Block;
+    use crate::world::{BlockPos, Realm, VoxelWorld};
+    use super::{find_portal, portal_destination, PortalShape};
+
+    fn build_frame(world: &VoxelWorld, corner: BlockPos, width: i32, height: i32, frame: Block) {
+        for (i, j) in itertools::iproduct!(-1..=width, -1..=height) {
+            if i == -1 || j == -1 || i == width || j == height {
+                world.set_block(corner + (0, j, i), frame);
+            }
+        }
+    }
+
+    #[test]
+    fn find_frame() {
+        let world = VoxelWorld::new();
+        let corner = BlockPos { x: 3, y: 70, z: -5, realm: Realm::Overworld };
+        build_frame(&world, corner, 3, 4, Block::Basalt);
+        let shape = PortalShape::find(&world, corner + (0, 2, 1), Block::Basalt).unwrap();
+        assert_eq!(shape, PortalShape { corner, along_x: false, width: 3, height: 4 });
+        assert!(PortalShape::find(&world, corner, Block::Glowstone).is_none());
+        // a hole in the frame
+        world.set_block(corner + (0, 4, 1), Block::Air);
+        assert!(PortalShape::find(&world, corner, Block::Basalt).is_none());
+    }
+
+    #[test]
+    fn build_and_find() {
+        let world = VoxelWorld::new();
+        let shape = PortalShape::build_at(&world, (100, 40), Realm::Nether, Block::Basalt);
+        assert!(shape.frame().all(|pos| world.get_block(pos) == Block::Basalt));
+        let found = find_portal(&world, BlockPos { x: 90, y: 0, z: 45, realm: Realm::Nether }, 16);
+        assert_eq!(found, Some(shape.corner));
+        world.set_block(shape.corner, Block::Air);
+        assert_eq!(find_portal(&world, shape.corner, 16), None);
+        assert_eq!(portal_destination(Block::Basalt, Realm::Nether), Some(Realm::Overworld));
+        assert_eq!(portal_destination(Block::Glowstone, Realm::Overworld), Some(Realm::Aether));
+    }
+}
@@ -1,6 +1,9 @@
 use bevy::prelude::Component;
+use serde::{Deserialize, Serialize};
+use strum_macros::EnumString;
 
-#[derive(Default, Debug, PartialEq, Eq, Clone, Copy, Hash, Component, PartialOrd, Ord)]
+#[derive(Default, Debug, PartialEq, Eq, Clone, Copy, Hash, Component, PartialOrd, Ord, EnumString, Serialize, Deserialize)]
+#[strum(ascii_case_insensitive)]
 pub enum Realm {
     #[default]
     Overworld,
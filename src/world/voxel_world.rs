@@ -1,11 +1,12 @@
 use super::{
     chunked, pos2d::chunks_in_col, BlockPos, BlockPos2d, Chunk, ChunkPos, ChunkedPos, ColPos,
-    ColedPos, Realm, CHUNK_S1, MAX_HEIGHT, Y_CHUNKS,
+    ColedPos, Climate, ColClimate, Realm, CHUNK_S1, MAX_HEIGHT, Y_CHUNKS,
 };
 use crate::Block;
 use bevy::prelude::{Resource, Vec3};
 use dashmap::DashMap;
 use std::{
+    collections::{HashMap, HashSet},
     ops::{Deref, DerefMut},
     sync::Arc,
 };
@@ -49,20 +50,56 @@ impl PartialEq for BlockRayCastHit {
     }
 }
 
-#[derive(Resource)]
+#[derive(Resource, Clone)]
 pub struct VoxelWorld {
     pub chunks: Arc<DashMap<ChunkPos, TrackedChunk>>,
+    pub climates: Arc<DashMap<ColPos, ColClimate>>,
+    /// Loot table of generated containers that haven't been opened yet
+    pub containers: Arc<DashMap<ColPos, HashMap<(usize, i32, usize), String>>>,
+    /// Bottom corner of the lit portals, so that arriving players don't have to scan for them
+    pub portals: Arc<DashMap<ColPos, HashSet<(usize, i32, usize)>>>,
 }
 
 impl VoxelWorld {
     pub fn new() -> Self {
         VoxelWorld {
             chunks: Arc::new(DashMap::new()),
+            climates: Arc::new(DashMap::new()),
+            containers: Arc::new(DashMap::new()),
+            portals: Arc::new(DashMap::new()),
         }
     }
 
-    pub fn new_with(chunks: Arc<DashMap<ChunkPos, TrackedChunk>>) -> Self {
-        VoxelWorld { chunks }
+    pub fn set_climate(&self, col_pos: ColPos, climate: ColClimate) {
+        self.climates.insert(col_pos, climate);
+    }
+
+    pub fn climate_at(&self, pos: BlockPos2d) -> Option<Climate> {
+        let (col_pos, pos2d) = pos.into();
+        self.climates.get(&col_pos).map(|climate| climate.get(pos2d))
+    }
+
+    pub fn tag_container(&self, pos: BlockPos, loot_table: &str) {
+        let (col_pos, pos) = pos.into();
+        self.containers.entry(col_pos).or_default().insert(pos, loot_table.to_string());
+    }
+
+    /// Removes and returns the loot table of the container, containers are only filled once
+    pub fn take_container_tag(&self, pos: BlockPos) -> Option<String> {
+        let (col_pos, pos) = pos.into();
+        self.containers.get_mut(&col_pos)?.remove(&pos)
+    }
+
+    pub fn tag_portal(&self, pos: BlockPos) {
+        let (col_pos, pos) = pos.into();
+        self.portals.entry(col_pos).or_default().insert(pos);
+    }
+
+    /// Portals tagged in the column, some may have been broken since
+    pub fn portals_in(&self, col_pos: ColPos) -> Vec<BlockPos> {
+        self.portals.get(&col_pos).map_or_else(Vec::new, |portals| {
+            portals.iter().map(|pos| BlockPos::from((col_pos, *pos))).collect()
+        })
     }
 
     pub fn set_block(&self, pos: BlockPos, block: Block) {
@@ -127,6 +164,19 @@ impl VoxelWorld {
         }
     }
 
+    /// Sets the block if `replaces` accepts the current one, checked and set atomically
+    pub fn set_block_if(&self, pos: BlockPos, block: Block, replaces: impl Fn(Block) -> bool) {
+        let (chunk_pos, chunked_pos) = <(ChunkPos, ChunkedPos)>::from(pos);
+        if self
+            .chunks
+            .entry(chunk_pos)
+            .or_insert_with(|| TrackedChunk::new())
+            .set_if(chunked_pos, block, replaces)
+        {
+            self.mark_change(chunk_pos, chunked_pos);
+        }
+    }
+
     pub fn get_block(&self, pos: BlockPos) -> Block {
         let (chunk_pos, chunked_pos) = <(ChunkPos, ChunkedPos)>::from(pos);
         match self.chunks.get(&chunk_pos) {
@@ -162,20 +212,29 @@ impl VoxelWorld {
         (Block::Air, 0)
     }
 
-    pub fn is_col_loaded(&self, player_pos: Vec3, realm: Realm) -> bool {
-        let (chunk_pos, _): (ChunkPos, _) = <BlockPos>::from((player_pos, realm)).into();
-        for y in (0..Y_CHUNKS as i32).rev() {
-            let chunk = ChunkPos {
-                x: chunk_pos.x,
+    pub fn bottom_block(&self, col_pos: ColPos, pos2d: ColedPos) -> Option<i32> {
+        // Lowest non-air block of the column, used to build underground layers below the surface
+        for y in 0..Y_CHUNKS as i32 {
+            let chunk_pos = ChunkPos {
+                x: col_pos.x,
                 y,
-                z: chunk_pos.z,
-                realm: chunk_pos.realm,
+                z: col_pos.z,
+                realm: col_pos.realm,
             };
-            if self.chunks.contains_key(&chunk) {
-                return true;
+            if let Some(block_y) = self.chunks.get(&chunk_pos).and_then(|chunk| chunk.bottom(pos2d)) {
+                return Some(y * CHUNK_S1 as i32 + block_y as i32);
             }
         }
-        false
+        None
+    }
+
+    pub fn is_col_loaded(&self, player_pos: Vec3, realm: Realm) -> bool {
+        let (col_pos, _): (ColPos, _) = <BlockPos>::from((player_pos, realm)).into();
+        self.has_col(col_pos)
+    }
+
+    pub fn has_col(&self, col_pos: ColPos) -> bool {
+        chunks_in_col(&col_pos).iter().rev().any(|chunk_pos| self.chunks.contains_key(chunk_pos))
     }
 
     pub fn mark_change_col(&self, col_pos: ColPos) {
@@ -195,6 +254,9 @@ impl VoxelWorld {
             };
             self.chunks.remove(&chunk_pos);
         }
+        self.climates.remove(&col);
+        self.containers.remove(&col);
+        self.portals.remove(&col);
     }
 
     pub fn mark_change_single(&self, chunk_pos: ChunkPos) {